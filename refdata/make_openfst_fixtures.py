@@ -0,0 +1,205 @@
+#!/usr/bin/env python3
+"""Writes the OpenFst binary fixtures refdata/openfst_*.fst used by
+test_wfst_io, from the AT&T text files in refdata.
+
+These files are NOT produced by OpenFst: this script writes the OpenFst
+on-disk layout (fst/fst.h FstHeader, fst/vector-fst.h, fst/const-fst.h
+and fst/symbol-table.cc) itself, independently of
+wfst::wfst_io::openfst, and computes all property bits as fstinfo
+does. It covers cases the OpenFst tools do not write by default
+(tropical64 and aligned const files). Files written by OpenFst itself
+(refdata/fstcompile_*.fst) come from make_openfst_reference.sh.
+
+Usage: python3 refdata/make_openfst_fixtures.py
+"""
+
+import os
+import struct
+
+REFDATA = os.path.dirname(os.path.abspath(__file__))
+
+FST_MAGIC = 2125659606
+SYMBOLTABLE_MAGIC = 2125658996
+HAS_ISYMBOLS = 0x1
+HAS_OSYMBOLS = 0x2
+IS_ALIGNED = 0x4
+EXPANDED = 0x1
+MUTABLE = 0x2
+
+# fst/properties.h, as (positive, negative) pairs
+ACCEPTOR = (0x10000, 0x20000)
+I_DETERMINISTIC = (0x40000, 0x80000)
+O_DETERMINISTIC = (0x100000, 0x200000)
+EPSILONS = (0x400000, 0x800000)
+I_EPSILONS = (0x1000000, 0x2000000)
+O_EPSILONS = (0x4000000, 0x8000000)
+I_LABEL_SORTED = (0x10000000, 0x20000000)
+O_LABEL_SORTED = (0x40000000, 0x80000000)
+WEIGHTED = (0x100000000, 0x200000000)
+CYCLIC = (0x400000000, 0x800000000)
+INITIAL_CYCLIC = (0x1000000000, 0x2000000000)
+TOP_SORTED = (0x4000000000, 0x8000000000)
+ACCESSIBLE = (0x10000000000, 0x20000000000)
+COACCESSIBLE = (0x40000000000, 0x80000000000)
+
+INF = float("inf")
+
+
+def read_symbols(filename):
+    syms = {}
+    with open(os.path.join(REFDATA, filename), encoding="utf-8") as f:
+        for line in f:
+            if line.strip():
+                sym, label = line.split()
+                syms[sym] = int(label)
+    return syms
+
+
+def read_att(filename, isyms=None, osyms=None):
+    """Returns (start, finals, arcs): finals maps states to weights,
+    arcs[state] lists (ilabel, olabel, weight, nextstate)."""
+    start, finals, arcs = None, {}, {}
+    with open(os.path.join(REFDATA, filename), encoding="utf-8") as f:
+        for line in f:
+            fields = line.split()
+            if not fields:
+                continue
+            src = int(fields[0])
+            if start is None:
+                start = src
+            arcs.setdefault(src, [])
+            if len(fields) <= 2:
+                finals[src] = float(fields[1]) if len(fields) == 2 else 0.0
+            else:
+                ilabel = isyms[fields[2]] if isyms else int(fields[2])
+                olabel = osyms[fields[3]] if osyms else int(fields[3])
+                weight = float(fields[4]) if len(fields) == 5 else 0.0
+                arcs[src].append((ilabel, olabel, weight, int(fields[1])))
+                arcs.setdefault(int(fields[1]), [])
+    nstates = max(arcs) + 1
+    return start, finals, [arcs.get(i, []) for i in range(nstates)]
+
+
+def properties(start, finals, arcs):
+    props = 0
+
+    def set_bit(pair, value):
+        nonlocal props
+        props |= pair[0] if value else pair[1]
+
+    every = [a for state in arcs for a in state]
+    set_bit(ACCEPTOR, all(i == o for i, o, _, _ in every))
+    set_bit(I_DETERMINISTIC, all(len({a[0] for a in s}) == len(s) for s in arcs))
+    set_bit(O_DETERMINISTIC, all(len({a[1] for a in s}) == len(s) for s in arcs))
+    set_bit(EPSILONS, any(i == 0 and o == 0 for i, o, _, _ in every))
+    set_bit(I_EPSILONS, any(i == 0 for i, _, _, _ in every))
+    set_bit(O_EPSILONS, any(o == 0 for _, o, _, _ in every))
+    set_bit(I_LABEL_SORTED, all([a[0] for a in s] == sorted(a[0] for a in s) for s in arcs))
+    set_bit(O_LABEL_SORTED, all([a[1] for a in s] == sorted(a[1] for a in s) for s in arcs))
+    set_bit(WEIGHTED, any(w not in (0.0, INF) for _, _, w, _ in every) or
+            any(w not in (0.0, INF) for w in finals.values()))
+    set_bit(TOP_SORTED, all(n > i for i, s in enumerate(arcs) for _, _, _, n in s))
+
+    def reachable(froms, edges):
+        seen, stack = set(froms), list(froms)
+        while stack:
+            for n in edges[stack.pop()]:
+                if n not in seen:
+                    seen.add(n)
+                    stack.append(n)
+        return seen
+
+    forward = [[a[3] for a in s] for s in arcs]
+    backward = [[] for _ in arcs]
+    for i, s in enumerate(arcs):
+        for _, _, _, n in s:
+            backward[n].append(i)
+    accessible = reachable([start], forward)
+    coaccessible = reachable(list(finals), backward)
+    set_bit(ACCESSIBLE, len(accessible) == len(arcs))
+    set_bit(COACCESSIBLE, len(coaccessible) == len(arcs))
+    cyclic = any(i in reachable(forward[i], forward) for i in range(len(arcs)))
+    set_bit(CYCLIC, cyclic)
+    set_bit(INITIAL_CYCLIC, start in reachable(forward[start], forward))
+    return props
+
+
+def string(s):
+    b = s.encode("utf-8")
+    return struct.pack("<i", len(b)) + b
+
+
+def symbol_table(name, syms):
+    out = struct.pack("<i", SYMBOLTABLE_MAGIC) + string(name)
+    out += struct.pack("<qq", max(syms.values()) + 1, len(syms))
+    for sym, label in sorted(syms.items(), key=lambda x: x[1]):
+        out += string(sym) + struct.pack("<q", label)
+    return out
+
+
+def write_fst(filename, fsttype, wfmt, arctype, fst, isyms=None, osyms=None, aligned=False):
+    start, finals, arcs = fst
+    wsize = struct.calcsize(wfmt)
+    static = EXPANDED | MUTABLE if fsttype == "vector" else EXPANDED
+    if fsttype == "vector":
+        version = 2
+    else:
+        version = 1 if aligned else 2
+    flags = (HAS_ISYMBOLS if isyms else 0) | (HAS_OSYMBOLS if osyms else 0) | (IS_ALIGNED if aligned else 0)
+    numarcs = sum(len(s) for s in arcs)
+
+    out = struct.pack("<i", FST_MAGIC) + string(fsttype) + string(arctype)
+    out += struct.pack("<iiQqqq", version, flags, properties(start, finals, arcs) | static,
+                       start, len(arcs), numarcs)
+    if isyms:
+        out += symbol_table(*isyms)
+    if osyms:
+        out += symbol_table(*osyms)
+
+    def arc(a, padded):
+        # struct ArcTpl {int ilabel; int olabel; Weight weight; int nextstate;},
+        # written field by field by VectorFst and as a struct by ConstFst
+        b = struct.pack("<ii" + wfmt[1:] + "i", a[0], a[1], a[2], a[3])
+        return b + b"\0" * (-len(b) % wsize) if padded else b
+
+    def align():
+        return b"\0" * (-len(out) % 16) if aligned else b""
+
+    if fsttype == "vector":
+        for i, s in enumerate(arcs):
+            out += struct.pack(wfmt, finals.get(i, INF)) + struct.pack("<q", len(s))
+            for a in s:
+                out += arc(a, False)
+    else:
+        out += align()
+        pos = 0
+        for i, s in enumerate(arcs):
+            # struct ConstState {Weight weight; Unsigned pos, narcs, niepsilons, noepsilons;}
+            out += struct.pack(wfmt, finals.get(i, INF))
+            out += struct.pack("<IIII", pos, len(s),
+                               sum(1 for a in s if a[0] == 0), sum(1 for a in s if a[1] == 0))
+            pos += len(s)
+        out += align()
+        for s in arcs:
+            for a in s:
+                out += arc(a, True)
+    with open(os.path.join(REFDATA, filename), "wb") as f:
+        f.write(out)
+
+
+def main():
+    isyms = read_symbols("isyms.txt")
+    osyms = read_symbols("osyms.txt")
+    fst_int = read_att("fst_int.txt")
+    fst_str = read_att("fst_str.txt", isyms, osyms)
+    write_fst("openfst_vector_standard.fst", "vector", "<f", "standard", fst_int)
+    write_fst("openfst_vector_log64_syms.fst", "vector", "<d", "log64", fst_str,
+              ("isyms.txt", isyms), ("osyms.txt", osyms))
+    write_fst("openfst_const_standard.fst", "const", "<f", "standard", fst_int)
+    write_fst("openfst_const_tropical64_syms.fst", "const", "<d", "tropical64", fst_str,
+              ("isyms.txt", isyms), ("osyms.txt", osyms))
+    write_fst("openfst_const_aligned_log.fst", "const", "<f", "log", fst_int, aligned=True)
+
+
+if __name__ == "__main__":
+    main()
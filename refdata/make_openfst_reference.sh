@@ -0,0 +1,18 @@
+#!/bin/sh
+# Writes the OpenFst reference files refdata/fstcompile_*.fst with
+# OpenFst's own command-line tools (fstcompile and fstconvert), to check
+# wfst::wfst_io::openfst against files produced by OpenFst itself.
+# test_wfst_io checks these files if they are present.
+#
+# Usage: sh refdata/make_openfst_reference.sh  (with OpenFst installed)
+
+set -e
+cd "$(dirname "$0")"
+
+fstcompile fst_int.txt fstcompile_vector_standard.fst
+fstcompile --arc_type=log64 \
+    --isymbols=isyms.txt --osymbols=osyms.txt --keep_isymbols --keep_osymbols \
+    fst_str.txt fstcompile_vector_log64_syms.fst
+fstconvert --fst_type=const fstcompile_vector_standard.fst fstcompile_const_standard.fst
+fstcompile --isymbols=isyms.txt --osymbols=osyms.txt --keep_isymbols --keep_osymbols fst_str.txt |
+    fstconvert --fst_type=const - fstcompile_const_standard_syms.fst
@@ -0,0 +1,184 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements mapping of the arcs and final weights of an
+//! Fst, which may change the weight type (semiring or precision), and
+//! some commonly used mappers.
+
+use super::super::semiring::{Weight};
+use super::super::semiring::float::Float;
+use super::super::semiring::floatweight::{ConvertFloatWeight, TropicalWeight};
+use super::super::semiring::latticeweight::LatticeWeight;
+use super::super::{ExpandedFst, MutableFst, Label, Arc};
+
+use std::marker::PhantomData;
+
+/// Maps arcs and final weights of weight type `W1` to `W2`.
+pub trait ArcMapper<W1: Weight, W2: Weight> {
+    fn map_weight(&self, weight: &W1) -> W2;
+
+    /// Maps the labels and weight of an arc, by default only mapping
+    /// the weight.
+    fn map_arc(&self, ilabel: Label, olabel: Label, weight: &W1) -> (Label, Label, W2) {
+        (ilabel, olabel, self.map_weight(weight))
+    }
+
+    /// Maps a final weight, by default as for arc weights.
+    fn map_final(&self, weight: &W1) -> W2 {
+        self.map_weight(weight)
+    }
+}
+
+/// Creates a new Fst by applying `mapper` to each arc and final weight
+/// of `ifst`, keeping the states, start state and symbol tables.
+pub fn arc_map<W1, W2, F, O, M> (ifst: &F, mapper: &M) -> O
+    where W1: Weight,
+          W2: Weight,
+          F: ExpandedFst<W1>,
+          O: MutableFst<W2>,
+          M: ArcMapper<W1, W2>,
+{
+    let mut ofst = O::new();
+    if let Some(isyms) = ifst.get_isyms() {
+        ofst.set_isyms(isyms);
+    }
+    if let Some(osyms) = ifst.get_osyms() {
+        ofst.set_osyms(osyms);
+    }
+    let nstates = ifst.get_numstates();
+    ofst.reserve_states(nstates);
+    for i in 0..nstates {
+        ofst.add_state(mapper.map_final(&ifst.get_finalweight(i)));
+    }
+    if let Some(start) = ifst.get_start() {
+        ofst.set_start(start);
+    }
+    for i in 0..nstates {
+        ofst.reserve_arcs(i, ifst.num_arcs(i));
+        for arc in ifst.arc_iter(i) {
+            let (ilabel, olabel, weight) = mapper.map_arc(arc.ilabel(), arc.olabel(), &arc.weight());
+            ofst.add_arc(i, arc.nextstate(), ilabel, olabel, weight);
+        }
+    }
+    ofst
+}
+
+/// Converts between float weight types with the same meaning (see
+/// `ConvertFloatWeight`), e.g. `TropicalWeight<f32>` to
+/// `LogWeight<f32>` (and back) or `LogWeight<f32>` to `LogWeight<f64>`.
+/// `zero()` and `one()` are mapped to `zero()` and `one()`, other
+/// values are copied.
+pub struct WeightConvertMapper<W1, W2> {
+    wtype: PhantomData<(W1, W2)>
+}
+
+impl<W1, W2> WeightConvertMapper<W1, W2> {
+    pub fn new() -> Self {
+        WeightConvertMapper {wtype: PhantomData}
+    }
+}
+
+impl<W1, W2> Default for WeightConvertMapper<W1, W2> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W1, W2> ArcMapper<W1, W2> for WeightConvertMapper<W1, W2>
+    where W1: ConvertFloatWeight<W2>,
+          W2: Weight,
+{
+    fn map_weight(&self, weight: &W1) -> W2 {
+        if *weight == W1::zero() {
+            W2::zero()
+        } else if *weight == W1::one() {
+            W2::one()
+        } else {
+            weight.convert_value()
+        }
+    }
+}
+
+/// Converts `ifst` to another float weight type using
+/// `WeightConvertMapper`.
+pub fn weight_convert<W1, W2, F, O> (ifst: &F) -> O
+    where W1: ConvertFloatWeight<W2>,
+          W2: Weight,
+          F: ExpandedFst<W1>,
+          O: MutableFst<W2>,
+{
+    arc_map(ifst, &WeightConvertMapper::<W1, W2>::new())
+}
+
+/// Quantizes all weights with the given `delta` (see `Weight::quantize`).
+pub struct QuantizeMapper {
+    pub delta: Option<f32>
+}
+
+impl<W: Weight> ArcMapper<W, W> for QuantizeMapper {
+    fn map_weight(&self, weight: &W) -> W {
+        weight.quantize(self.delta)
+    }
+}
+
+/// Replaces all weights `w` with `one() / w` (non-final states remain
+/// non-final).
+pub struct InvertWeightMapper;
+
+impl<W: Weight> ArcMapper<W, W> for InvertWeightMapper {
+    fn map_weight(&self, weight: &W) -> W {
+        W::one().divide(weight, None)
+    }
+
+    fn map_final(&self, weight: &W) -> W {
+        if *weight == W::zero() {
+            W::zero()
+        } else {
+            self.map_weight(weight)
+        }
+    }
+}
+
+/// Removes weights: arc weights become `one()`, as do final weights
+/// of final states.
+pub struct RmWeightMapper;
+
+impl<W: Weight> ArcMapper<W, W> for RmWeightMapper {
+    #[allow(unused_variables)]
+    fn map_weight(&self, weight: &W) -> W {
+        W::one()
+    }
+
+    fn map_final(&self, weight: &W) -> W {
+        if *weight == W::zero() {
+            W::zero()
+        } else {
+            W::one()
+        }
+    }
+}
+
+/// Combines the graph and acoustic costs of lattice weights into
+/// `TropicalWeight`s as `lm_scale * graph + acoustic_scale * acoustic`
+/// (see `LatticeWeight::scaled_cost()`).
+pub struct LatticeScaleMapper<T> {
+    pub lm_scale: T,
+    pub acoustic_scale: T,
+}
+
+impl<T: Float<T>> LatticeScaleMapper<T> {
+    pub fn new(lm_scale: T, acoustic_scale: T) -> Self {
+        LatticeScaleMapper {lm_scale, acoustic_scale}
+    }
+}
+
+impl<T: Float<T>> ArcMapper<LatticeWeight<T>, TropicalWeight<T>> for LatticeScaleMapper<T> {
+    fn map_weight(&self, weight: &LatticeWeight<T>) -> TropicalWeight<T> {
+        TropicalWeight::new(weight.scaled_cost(self.lm_scale.clone(), self.acoustic_scale.clone()))
+    }
+}
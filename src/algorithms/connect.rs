@@ -33,6 +33,7 @@ use std::vec::Vec;
 
 use super::super::semiring::{Weight};
 use super::super::{Fst, ExpandedFst, MutableFst, StateId, Arc};
+use super::super::properties::{ACCESSIBLE, NOT_ACCESSIBLE, COACCESSIBLE, NOT_COACCESSIBLE};
 
 /// Copies a path
 fn duplicate_path(last_path_idx: usize, from: StateId, to: StateId, paths: &mut Vec<Vec<StateId>>) {
@@ -114,7 +115,11 @@ fn dfs<W: Weight, F: ExpandedFst<W>> (fst: &F) -> (HashSet<StateId>, HashSet<Sta
     let mut paths = Vec::<Vec<StateId>>::new();
     paths.push(Vec::new());
 
-    let currstate = fst.get_start().unwrap();
+    //without a start state no states are accessible
+    let currstate = match fst.get_start() {
+        Some(s) => s,
+        None => return (accessible, coaccessible),
+    };
     
     if !accessible.contains(&currstate) {
         dfsnext(fst, currstate, &mut paths, &mut explored_arcs, &mut accessible);
@@ -133,6 +138,10 @@ fn dfs<W: Weight, F: ExpandedFst<W>> (fst: &F) -> (HashSet<StateId>, HashSet<Sta
 /// Trims an fst, removing states and arcs that are not on a
 /// successful path
 pub fn connect<W: Weight, F: ExpandedFst<W> + MutableFst<W>> (mut fst: F) -> F {
+    let mask = ACCESSIBLE | COACCESSIBLE;
+    if fst.properties(mask, false) == mask {
+        return fst
+    }
     let (accessible, coaccessible) = dfs(&fst);
     let mut to_delete = Vec::<StateId>::new();
 
@@ -142,5 +151,6 @@ pub fn connect<W: Weight, F: ExpandedFst<W> + MutableFst<W>> (mut fst: F) -> F {
         }
     }
     fst.del_states(to_delete);
+    fst.set_properties(mask, ACCESSIBLE | NOT_ACCESSIBLE | COACCESSIBLE | NOT_COACCESSIBLE);
     fst
 }
@@ -0,0 +1,110 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements the conversion of transducers to and from
+//! acceptors over `GallicWeight`s, in which output labels are moved
+//! into the weights. This is the representation used to determinize
+//! transducers and push labels.
+
+use super::super::semiring::{Weight};
+use super::super::semiring::stringweight::{StringWeight, GallicWeight};
+use super::super::{ExpandedFst, MutableFst, StateId, Label, Arc};
+
+/// Converts a transducer to an acceptor over input labels with each
+/// output label moved into the string part of the arc's
+/// `GallicWeight`.
+pub fn to_gallic<W: Weight, F: ExpandedFst<W>, O: MutableFst<GallicWeight<W>>> (ifst: &F) -> O {
+    let mut ofst = O::new();
+    if let Some(isyms) = ifst.get_isyms() {
+        ofst.set_isyms(isyms);
+    }
+    if let Some(osyms) = ifst.get_osyms() {
+        ofst.set_osyms(osyms);
+    }
+    let nstates = ifst.get_numstates();
+    ofst.reserve_states(nstates);
+    for i in 0..nstates {
+        let finalweight = ifst.get_finalweight(i);
+        if finalweight == W::zero() {
+            ofst.add_state(GallicWeight::zero());
+        } else {
+            ofst.add_state(GallicWeight::new(StringWeight::one(), finalweight));
+        }
+    }
+    if let Some(start) = ifst.get_start() {
+        ofst.set_start(start);
+    }
+    for i in 0..nstates {
+        for arc in ifst.arc_iter(i) {
+            let weight = GallicWeight::new(StringWeight::from_label(arc.olabel()), arc.weight());
+            ofst.add_arc(i, arc.nextstate(), arc.ilabel(), arc.ilabel(), weight);
+        }
+    }
+    ofst
+}
+
+/// Adds a path of epsilon-input arcs from `source` to `target` with
+/// the given output labels (at least one) and weight on the first arc.
+fn add_string_path<W: Weight, O: MutableFst<W>> (ofst: &mut O, source: StateId, target: StateId, ilabel: Label, olabels: &[Label], weight: W) {
+    let mut s = source;
+    let mut ilabel = ilabel;
+    let mut weight = weight;
+    for (k, olabel) in olabels.iter().enumerate() {
+        let ns = if k == olabels.len() - 1 {
+            target
+        } else {
+            ofst.add_state(W::zero())
+        };
+        ofst.add_arc(s, ns, ilabel, *olabel, weight);
+        s = ns;
+        ilabel = 0;
+        weight = W::one();
+    }
+}
+
+/// Converts an acceptor over `GallicWeight`s back to a transducer,
+/// moving the strings of the weights to output labels. Strings longer
+/// than one label are expanded into paths through new states.
+pub fn from_gallic<W: Weight, F: ExpandedFst<GallicWeight<W>>, O: MutableFst<W>> (ifst: &F) -> O {
+    let mut ofst = O::new();
+    if let Some(isyms) = ifst.get_isyms() {
+        ofst.set_isyms(isyms);
+    }
+    if let Some(osyms) = ifst.get_osyms() {
+        ofst.set_osyms(osyms);
+    }
+    let nstates = ifst.get_numstates();
+    ofst.reserve_states(nstates);
+    for _ in 0..nstates {
+        ofst.add_state(W::zero());
+    }
+    if let Some(start) = ifst.get_start() {
+        ofst.set_start(start);
+    }
+    for i in 0..nstates {
+        for arc in ifst.arc_iter(i) {
+            let weight = arc.weight();
+            let olabels = weight.value1().labels().unwrap_or(&[]);
+            if olabels.len() <= 1 {
+                let olabel = olabels.first().cloned().unwrap_or(0);
+                ofst.add_arc(i, arc.nextstate(), arc.ilabel(), olabel, weight.value2().clone());
+            } else {
+                add_string_path(&mut ofst, i, arc.nextstate(), arc.ilabel(), olabels, weight.value2().clone());
+            }
+        }
+        let finalweight = ifst.get_finalweight(i);
+        let olabels = finalweight.value1().labels().unwrap_or(&[]);
+        if finalweight.value2() == &W::zero() || olabels.is_empty() {
+            ofst.set_finalweight(i, finalweight.value2().clone());
+        } else {
+            let newfinal = ofst.add_state(W::one());
+            add_string_path(&mut ofst, i, newfinal, 0, olabels, finalweight.value2().clone());
+        }
+    }
+    ofst
+}
@@ -30,7 +30,7 @@
 //      type requirements, e.g. have `reverse` work for any input fst
 //      type.
 use super::semiring::{Weight};
-use super::{ExpandedFst, MutableFst, StateId, Arc};
+use super::{ExpandedFst, MutableFst, StateId, Arc, Error};
 
 use std::vec::Vec;
 
@@ -84,7 +84,16 @@ pub fn unextendfinal<W: Weight, F: ExpandedFst<W> + MutableFst<W>> (mut fst: F)
 /// Reverses an `Fst`: If the input fst transduces string x to y with
 /// weight a, then the reverse transduces the reverse of x to the
 /// reverse of y with weight a.reverse().
-pub fn reverse<W: Weight, F: ExpandedFst<W> + MutableFst<W>, O: MutableFst<W>> (mut ifst: F) -> O {
+///
+/// Panics if the input has no start state (see `try_reverse()`).
+pub fn reverse<W: Weight, F: ExpandedFst<W> + MutableFst<W>, O: MutableFst<W>> (ifst: F) -> O {
+    try_reverse(ifst).unwrap()
+}
+
+/// Like `reverse()`, but returns an error if the input has no start
+/// state.
+pub fn try_reverse<W: Weight, F: ExpandedFst<W> + MutableFst<W>, O: MutableFst<W>> (mut ifst: F) -> Result<O, Error> {
+    let startstate = ifst.get_start().ok_or(Error::NoStartState)?;
     ifst = extendfinal(ifst);
     //Swap symbol tables
     let mut ofst = O::new();
@@ -101,15 +110,17 @@ pub fn reverse<W: Weight, F: ExpandedFst<W> + MutableFst<W>, O: MutableFst<W>> (
             ofst.set_start(i);
         }
     }
-    ofst.set_finalweight(ifst.get_start().unwrap(), W::one());
+    ofst.set_finalweight(startstate, W::one());
     //Create reversed arcs
     for i in 0..ifst.get_numstates() {
         for arc in ifst.arc_iter(i) {
             ofst.add_arc(arc.nextstate(), i, arc.ilabel(), arc.olabel(), arc.weight().reverse())
         }
     }    
-    ofst
+    Ok(ofst)
 }
 
 pub mod shortestpath;
 pub mod connect;
+pub mod gallic;
+pub mod arcmap;
@@ -32,55 +32,35 @@
 //! See the source file `example_shortestpath.rs` for a simple example
 //! of intended use.
 
-extern crate serde;
-use self::serde::Serialize;
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-extern crate bincode;
-use self::bincode::{serialize};
-
 use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::fmt::Debug;
 
-use super::super::semiring::{Weight, NaturalLess};
-use super::super::{Fst, ExpandedFst, MutableFst, StateId, Arc};
+use super::super::semiring::{Weight, NaturalLess, natural_cmp};
+use super::super::{Fst, ExpandedFst, MutableFst, StateId, Arc, Error};
 use super::super::utils::{LinkedHashSet, ComparatorHeap};
 use super::super::wfst_vec::VecFst;
-use super::{extendfinal, reverse};
-
-fn hash<T: Hash + Debug>(obj: T) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    obj.hash(&mut hasher);
-    hasher.finish()
-    // let a = hasher.finish();
-    // println!("{:?}\t{:?}", obj, a);
-    // a
-}
+use super::{extendfinal, try_reverse};
 
 ////////////////////////////////////////////////////////////////////////////////
-// (state, weight) tuple with Ord trait implementation for use in
-// shortest_paths()
+// (state, weight, index) tuple for use in shortest_paths(), the index
+// identifies the path prefix in the bookkeeping vectors since weights
+// cannot be hashed or compared reliably
 #[derive(Clone, Debug)]
-struct Pair<W: Weight + Serialize>(StateId, W);
-
-impl<W: Weight + Serialize> PartialEq for Pair<W> {
-    fn eq(&self, rhs: &Self) -> bool {
-        hash(self) == hash(rhs)
-    }
-}
-impl<W: Weight + Serialize> Eq for Pair<W> {}
-impl<W: Weight + Serialize> Hash for Pair<W> {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.0.hash(state);
-        serialize(&self.1).unwrap().hash(state);
-    }
-}
+struct Pair<W: Weight>(StateId, W, usize);
 ////////////////////////////////////////////////////////////////////////////////
 
 /// Calculates the shortest distances from each state to the final
-pub fn shortest_distance<W: Weight + Serialize, F: ExpandedFst<W> + MutableFst<W>> (ifst: F) -> Vec<W> {
-    let revfst: VecFst<_> = reverse(ifst);
+///
+/// Panics if the input has no start state (see
+/// `try_shortest_distance()`).
+pub fn shortest_distance<W: Weight, F: ExpandedFst<W> + MutableFst<W>> (ifst: F) -> Vec<W> {
+    try_shortest_distance(ifst).unwrap()
+}
+
+/// Like `shortest_distance()`, but returns an error if the input has no
+/// start state.
+pub fn try_shortest_distance<W: Weight, F: ExpandedFst<W> + MutableFst<W>> (ifst: F) -> Result<Vec<W>, Error> {
+    let revfst: VecFst<_> = try_reverse(ifst)?;
+    let startstate = revfst.get_start().ok_or(Error::NoStartState)?;
     let nstates = revfst.get_numstates();
 
     let mut d: Vec<W> = Vec::with_capacity(nstates);
@@ -89,10 +69,10 @@ pub fn shortest_distance<W: Weight + Serialize, F: ExpandedFst<W> + MutableFst<W
     r.resize(nstates, W::zero());
     
     let mut queue = LinkedHashSet::new();
-    queue.insert(revfst.get_start().unwrap());
+    queue.insert(startstate);
 
-    d[revfst.get_start().unwrap()] = W::one();
-    r[revfst.get_start().unwrap()] = W::one();
+    d[startstate] = W::one();
+    r[startstate] = W::one();
 
     while !queue.is_empty() {
         let s = queue.pop_front().unwrap();
@@ -119,11 +99,19 @@ pub fn shortest_distance<W: Weight + Serialize, F: ExpandedFst<W> + MutableFst<W
         }
     }
     //println!("{:?}", d);
-    d
+    Ok(d)
 }
 
 /// Calculates the n-best shortest path from the initial to the final state
-pub fn shortest_paths<W: Weight + NaturalLess + Serialize, F: ExpandedFst<W> + MutableFst<W>, O: MutableFst<W>> (mut ifst: F, n: usize, det: bool) -> O {
+///
+/// Panics if the input has no start state (see `try_shortest_paths()`).
+pub fn shortest_paths<W: Weight + NaturalLess, F: ExpandedFst<W> + MutableFst<W>, O: MutableFst<W>> (ifst: F, n: usize, det: bool) -> O {
+    try_shortest_paths(ifst, n, det).unwrap()
+}
+
+/// Like `shortest_paths()`, but returns an error if the input has no
+/// start state.
+pub fn try_shortest_paths<W: Weight + NaturalLess, F: ExpandedFst<W> + MutableFst<W>, O: MutableFst<W>> (mut ifst: F, n: usize, det: bool) -> Result<O, Error> {
     ifst = if det {
         println!("Determinize not yet implemented!");
         ifst
@@ -140,17 +128,11 @@ pub fn shortest_paths<W: Weight + NaturalLess + Serialize, F: ExpandedFst<W> + M
         ofst.set_isyms(isyms);
     }
     
-    let d = shortest_distance(ifst.clone());
+    let d = try_shortest_distance(ifst.clone())?;
     let compare = |p1: &Pair<W>, p2: &Pair<W>| -> Ordering {
         let a1 = p1.1.times(&d[p1.0]);
         let a2 = p2.1.times(&d[p2.0]);
-        if a1.eq(&a2) {  //demit: or use approx_eq()
-            Ordering::Equal
-        } else if a1.natural_less(&a2) {
-            Ordering::Less
-        } else {
-            Ordering::Greater
-        }
+        natural_cmp(&a1, &a2)  //demit: or use approx_eq()
     };
     ifst = extendfinal(ifst);
     let nstates = ifst.get_numstates();
@@ -158,13 +140,16 @@ pub fn shortest_paths<W: Weight + NaturalLess + Serialize, F: ExpandedFst<W> + M
     r.resize(nstates, 0);
     
     let mut queue = ComparatorHeap::new(&compare); //Priority Queue
-    let mut previous = HashMap::<Pair<W>, Option<Pair<W>>>::with_capacity(nstates);
-    let mut statemap = HashMap::<Pair<W>, StateId>::with_capacity(nstates);
-    
-    let i = ifst.get_start().unwrap();
-    let pair = Pair(i, W::one());
-    queue.push(pair.clone());
-    previous.insert(pair.clone(), None);
+    //for each pair index: the index and state of the previous pair
+    //and the index of the arc taken from it, and the output state once
+    //popped
+    let mut previous: Vec<Option<(usize, StateId, usize)>> = Vec::with_capacity(nstates);
+    let mut statemap: Vec<Option<StateId>> = Vec::with_capacity(nstates);
+
+    let i = ifst.get_start().ok_or(Error::NoStartState)?;
+    queue.push(Pair(i, W::one(), 0));
+    previous.push(None);
+    statemap.push(None);
 
     while !queue.is_empty() {
         //println!("{:?}", r);
@@ -181,26 +166,20 @@ pub fn shortest_paths<W: Weight + NaturalLess + Serialize, F: ExpandedFst<W> + M
         //     queue.push(v.pop().unwrap());
         // }
         // ////
-        let pair = queue.pop().unwrap();
-        let Pair(p, c) = pair.clone();
+        let Pair(p, c, index) = queue.pop().unwrap();
         //println!("{:?} {:?}", p, c.times(&d[p]));
 
         let np = ofst.add_state(ifst.get_finalweight(p));
-        statemap.insert(pair.clone(), np);
+        statemap[index] = Some(np);
 
-        let _ppair = previous.get(&pair).unwrap().clone();
-        if _ppair.is_none() {
+        match previous[index] {
             //this is the start state
-            ofst.set_start(np);
-        } else {
+            None => ofst.set_start(np),
             //add the incoming arc from previous to current
-            let ppair = _ppair.unwrap();
-            let pp = *statemap.get(&ppair).unwrap();
-            let opp = ppair.0;
-            for arc in ifst.arc_iter(opp) {
-                if arc.nextstate() == p {
-                    ofst.add_arc(pp, np, arc.ilabel(), arc.olabel(), arc.weight());
-                }
+            Some((pindex, opp, arcindex)) => {
+                let pp = statemap[pindex].unwrap();
+                let arc = ifst.arc_iter(opp).nth(arcindex).unwrap();
+                ofst.add_arc(pp, np, arc.ilabel(), arc.olabel(), arc.weight());
             }
         }
 
@@ -210,13 +189,13 @@ pub fn shortest_paths<W: Weight + NaturalLess + Serialize, F: ExpandedFst<W> + M
         }
 
         if r[p] <= n {
-            for arc in ifst.arc_iter(p) {
+            for (arcindex, arc) in ifst.arc_iter(p).enumerate() {
                 let nc = c.times(&arc.weight());
-                let npair = Pair(arc.nextstate(), nc);
-                previous.insert(npair.clone(), Some(pair.clone()));
-                queue.push(npair.clone());
+                queue.push(Pair(arc.nextstate(), nc, previous.len()));
+                previous.push(Some((index, p, arcindex)));
+                statemap.push(None);
             }
         }
     }
-    ofst
+    Ok(ofst)
 }
@@ -0,0 +1,138 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements the error type returned by fallible
+//! operations: reading and writing Fsts and symbol tables, and the
+//! `try_` variants of `MutableFst` operations and algorithms (which
+//! return errors on bad input where the other variants panic).
+
+extern crate bincode;
+extern crate serde_json;
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::string::FromUtf8Error;
+
+use super::StateId;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A state id that is not a state of the Fst
+    InvalidState(StateId),
+    /// An arc index that is not an arc of the state
+    InvalidArc(StateId, usize),
+    /// The Fst has no start state, but the operation requires one
+    NoStartState,
+    /// Malformed input, with the line number in text formats
+    Format{line: Option<usize>, message: String},
+    /// An Fst of a type other than the one expected (or supported)
+    UnsupportedType{fsttype: String, wtype: String},
+    /// A weight type not supported by a file format, or other than the
+    /// one expected
+    UnsupportedWeight(String),
+    /// Another feature (e.g. a file format version) not supported
+    Unsupported(String),
+    /// A native file written before format version 1, which does not
+    /// record its weight type, read without naming the type (see
+    /// `wfst_io::deserialise()`)
+    LegacyFile,
+    /// A key added to an archive more than once
+    DuplicateKey(String),
+    /// A key not found in an archive
+    KeyNotFound(String),
+    /// An invalid argument or option
+    Invalid(String),
+    /// An error in the archive entry with `key`
+    Entry{key: String, error: Box<Error>},
+    /// An error reading or writing
+    Io(io::Error),
+}
+
+impl Error {
+    /// A `Format` error at line `line`
+    pub fn format_at(line: usize, message: &str) -> Self {
+        Error::Format{line: Some(line), message: String::from(message)}
+    }
+
+    /// A `Format` error without a line number
+    pub fn format(message: &str) -> Self {
+        Error::Format{line: None, message: String::from(message)}
+    }
+
+    /// Wraps the error as an error in the archive entry with `key`
+    pub fn in_entry(self, key: &str) -> Self {
+        Error::Entry{key: String::from(key), error: Box::new(self)}
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidState(id) => write!(f, "Invalid state id {}", id),
+            Error::InvalidArc(id, index) => write!(f, "Invalid arc index {} of state {}", index, id),
+            Error::NoStartState => write!(f, "Fst has no start state"),
+            Error::Format{line: Some(line), ref message} => write!(f, "Format error: line {}: {}", line, message),
+            Error::Format{line: None, ref message} => write!(f, "Format error: {}", message),
+            Error::UnsupportedType{ref fsttype, ref wtype} => write!(f, "Unsupported type: {} Fst with {} weights", fsttype, wtype),
+            Error::UnsupportedWeight(ref wtype) => write!(f, "Unsupported weight type: {}", wtype),
+            Error::Unsupported(ref what) => write!(f, "Unsupported: {}", what),
+            Error::LegacyFile => write!(f, "Native file written before format version 1: the weight type must be given to read it"),
+            Error::DuplicateKey(ref key) => write!(f, "Duplicate key '{}'", key),
+            Error::KeyNotFound(ref key) => write!(f, "Key '{}' not found", key),
+            Error::Invalid(ref what) => write!(f, "Invalid argument: {}", what),
+            Error::Entry{ref key, ref error} => write!(f, "{} (key '{}')", error, key),
+            Error::Io(ref e) => write!(f, "IO error: {}", e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Entry{ref error, ..} => Some(&**error),
+            Error::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<bincode::Error> for Error {
+    fn from(e: bincode::Error) -> Error {
+        match *e {
+            // Input ending within a value is malformed
+            bincode::ErrorKind::Io(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => Error::format("unexpected end of input"),
+            bincode::ErrorKind::Io(e) => Error::Io(e),
+            // Lengths are limited by the size of the input
+            bincode::ErrorKind::SizeLimit => Error::format("length exceeds the size of the input"),
+            e => Error::format(&e.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        if e.is_io() {
+            Error::Io(e.into())
+        } else {
+            Error::format(&e.to_string())
+        }
+    }
+}
+
+impl From<FromUtf8Error> for Error {
+    fn from(e: FromUtf8Error) -> Error {
+        Error::format(&e.to_string())
+    }
+}
@@ -11,6 +11,7 @@ extern crate wfst;
 use wfst::semiring::Weight;
 use wfst::semiring::floatweight::TropicalWeight;
 use wfst::{MutableFst};
+use wfst::symboltable::SymbolTable;
 use wfst::wfst_vec::VecFst;
 use wfst::algorithms::shortestpath::shortest_paths;
 use wfst::algorithms::connect::connect;
@@ -28,8 +29,8 @@ fn main() {
     // let s2 = fst.add_state(TropicalWeight::zero());
     // let s3 = fst.add_state(TropicalWeight::one());
     // fst.set_start(s0);
-    // fst.set_isyms(vec!("", "a", "b", "c", "d").iter().map(|x| String::from(*x)));
-    // fst.set_osyms(vec!("", "a", "b", "c", "d").iter().map(|x| String::from(*x)));
+    // fst.set_isyms(vec!("", "a", "b", "c", "d").iter().map(|x| String::from(*x)).collect::<SymbolTable>());
+    // fst.set_osyms(vec!("", "a", "b", "c", "d").iter().map(|x| String::from(*x)).collect::<SymbolTable>());
     // fst.add_arc(s0, s1, 1, 1, TropicalWeight::new(Some(1.0)));
     // fst.add_arc(s0, s2, 2, 2, TropicalWeight::new(Some(1.0)));
     // fst.add_arc(s1, s3, 3, 3, TropicalWeight::new(Some(4.0)));
@@ -50,8 +51,8 @@ fn main() {
     // let s2 = fst.add_state(TropicalWeight::zero());
     // let s3 = fst.add_state(TropicalWeight::new(Some(3.0)));
     // fst.set_start(s0);
-    // fst.set_isyms(vec!("", "a", "b", "c", "d", "f").iter().map(|x| String::from(*x)));
-    // fst.set_osyms(vec!("", "a", "b", "c", "d", "f").iter().map(|x| String::from(*x)));
+    // fst.set_isyms(vec!("", "a", "b", "c", "d", "f").iter().map(|x| String::from(*x)).collect::<SymbolTable>());
+    // fst.set_osyms(vec!("", "a", "b", "c", "d", "f").iter().map(|x| String::from(*x)).collect::<SymbolTable>());
     // fst.add_arc(s0, s1, 1, 1, TropicalWeight::new(Some(3.0)));
     // fst.add_arc(s0, s2, 4, 4, TropicalWeight::new(Some(5.0)));
     // fst.add_arc(s1, s1, 2, 2, TropicalWeight::new(Some(2.0)));
@@ -71,8 +72,8 @@ fn main() {
     let s2 = fst.add_state(TropicalWeight::zero());
     let s3 = fst.add_state(TropicalWeight::one());
     fst.set_start(s0);
-    fst.set_isyms(vec!("", "versekeringsaandeel", "versekering", "saandeel", "s", "aandeel").iter().map(|x| String::from(*x)));
-    fst.set_osyms(vec!("", "versekeringsaandeel", "versekering", "saandeel", "s", "aandeel").iter().map(|x| String::from(*x)));
+    fst.set_isyms(vec!("", "versekeringsaandeel", "versekering", "saandeel", "s", "aandeel").iter().map(|x| String::from(*x)).collect::<SymbolTable>());
+    fst.set_osyms(vec!("", "versekeringsaandeel", "versekering", "saandeel", "s", "aandeel").iter().map(|x| String::from(*x)).collect::<SymbolTable>());
     fst.add_arc(s0, s3, 1, 1, TropicalWeight::new(Some(0.0)));
     fst.add_arc(s0, s1, 2, 2, TropicalWeight::new(Some(-1.0)));
     fst.add_arc(s1, s3, 3, 3, TropicalWeight::new(Some(1.0)));
@@ -9,11 +9,14 @@
 extern crate wfst;
 
 use wfst::semiring::Weight;
-use wfst::semiring::floatweight::TropicalWeight;
-use wfst::{Fst, MutableFst, ExpandedFst, Arc};
+use wfst::semiring::floatweight::{TropicalWeight, LogWeight};
+use wfst::{Fst, MutableFst, ExpandedFst, Arc, MutableArc};
+use wfst::symboltable::SymbolTable;
 use wfst::wfst_vec::{StdArc, VecFst};
 use wfst::algorithms;
-use wfst::wfst_io::{serialise, deserialise, deserialise_wrapper};
+use wfst::algorithms::arcmap::{arc_map, weight_convert, InvertWeightMapper};
+use wfst::wfst_io::{serialise, serialise_into, deserialise, deserialise_from, deserialise_wrapper};
+use wfst::wfst_io::compression::{Compression, CompressedWriter};
 
 
 fn main() {
@@ -53,6 +56,7 @@ fn main() {
     println!("{:?}", fst);
     println!("");
     println!("Number of states: {}", fst.get_numstates());    
+    println!("Number of arcs: {}", fst.total_arcs());
     println!("==============================");
     println!("");
     fst = algorithms::extendfinal(fst);
@@ -92,10 +96,23 @@ fn main() {
     fst.add_arc(s0, s1, 1, 4, TropicalWeight::<f32>::one());
     fst.add_arc(s1, s2, 2, 5, TropicalWeight::<f32>::new(Some(0.4)));
     fst.add_arc(s1, s2, 3, 6, TropicalWeight::<f32>::zero());
-    fst.set_isyms(vec!("<eps>", "a", "b", "c").iter().map(|x| String::from(*x)));
-    fst.set_osyms(vec!("<eps>", "", "", "", "x", "y", "z").iter().map(|x| String::from(*x)));
+    fst.set_isyms(vec!("<eps>", "a", "b", "c").iter().map(|x| String::from(*x)).collect::<SymbolTable>());
+    fst.set_osyms(vec!("<eps>", "", "", "", "x", "y", "z").iter().map(|x| String::from(*x)).collect::<SymbolTable>());
     println!("{}", fst);
     println!("==============================");
+    for mut arc in fst.arc_iter_mut(s1) {
+        let w = arc.weight().times(&TropicalWeight::new(Some(1.0)));
+        arc.set_weight(w);
+    }
+    fst.set_arc(s0, 0, s1, 1, 5, TropicalWeight::new(Some(0.1)));
+    fst.del_arc(s1, 1);
+    println!("{}", fst);
+    println!("==============================");
+    let logfst: VecFst<LogWeight<f64>> = weight_convert(&fst);
+    println!("{}", logfst);
+    let invfst: VecFst<LogWeight<f64>> = arc_map(&logfst, &InvertWeightMapper);
+    println!("{}", invfst);
+    println!("==============================");
     let a = serialise(&fst).unwrap();
     println!("{:?}", a);
     println!("==============================");
@@ -104,4 +121,11 @@ fn main() {
     println!("==============================");
     let c = deserialise_wrapper(&a).unwrap();
     println!("{:?}", c);
+    println!("==============================");
+    let mut writer = CompressedWriter::new(Vec::new(), Compression::Gzip).unwrap();
+    serialise_into(&fst, &mut writer).unwrap();
+    let d = writer.finish().unwrap();
+    println!("Compressed: {} bytes (uncompressed {} bytes)", d.len(), a.len());
+    let e: VecFst<TropicalWeight<f32>> = deserialise_from(&d[..]).unwrap();
+    println!("{}", e);
 }
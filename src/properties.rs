@@ -0,0 +1,411 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+// This file contains portions of code ported from OpenFst
+// (http://www.openfst.org) under the following licence and
+// attribution:
+//
+// """
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Copyright 2005-2010 Google, Inc.
+// Author: riley@google.com (Michael Riley)
+// """
+////////////////////////////////////////////////////////////////////////////////
+
+//! This module defines the property bits which describe facts about
+//! an `Fst` (e.g. whether it is an acceptor or acyclic) and functions
+//! to compute them.
+//!
+//! Properties come in pairs of a positive and a negative bit
+//! (e.g. `ACCEPTOR` and `NOT_ACCEPTOR`): a property is *known* when
+//! either bit of its pair is set and unknown when neither is. Fst
+//! implementations may cache known properties and update them
+//! conservatively when mutated (see `ExpandedFst::properties()`).
+
+use std::collections::HashSet;
+
+use super::semiring::Weight;
+use super::{ExpandedFst, StateId, Label, Arc};
+
+pub type Properties = u64;
+
+/// ilabel == olabel for each arc
+pub const ACCEPTOR: Properties = 1 << 0;
+/// ilabel != olabel for some arc
+pub const NOT_ACCEPTOR: Properties = 1 << 1;
+/// ilabels unique leaving each state
+pub const I_DETERMINISTIC: Properties = 1 << 2;
+/// ilabels not unique leaving some state
+pub const NON_I_DETERMINISTIC: Properties = 1 << 3;
+/// olabels unique leaving each state
+pub const O_DETERMINISTIC: Properties = 1 << 4;
+/// olabels not unique leaving some state
+pub const NON_O_DETERMINISTIC: Properties = 1 << 5;
+/// FST has input/output epsilons
+pub const EPSILONS: Properties = 1 << 6;
+/// FST has no input/output epsilons
+pub const NO_EPSILONS: Properties = 1 << 7;
+/// FST has input epsilons
+pub const I_EPSILONS: Properties = 1 << 8;
+/// FST has no input epsilons
+pub const NO_I_EPSILONS: Properties = 1 << 9;
+/// FST has output epsilons
+pub const O_EPSILONS: Properties = 1 << 10;
+/// FST has no output epsilons
+pub const NO_O_EPSILONS: Properties = 1 << 11;
+/// ilabels sorted wrt < for each state
+pub const I_LABEL_SORTED: Properties = 1 << 12;
+/// ilabels not sorted wrt < for some state
+pub const NOT_I_LABEL_SORTED: Properties = 1 << 13;
+/// olabels sorted wrt < for each state
+pub const O_LABEL_SORTED: Properties = 1 << 14;
+/// olabels not sorted wrt < for some state
+pub const NOT_O_LABEL_SORTED: Properties = 1 << 15;
+/// Non-trivial arc or final weights
+pub const WEIGHTED: Properties = 1 << 16;
+/// Only trivial arc and final weights
+pub const UNWEIGHTED: Properties = 1 << 17;
+/// FST has cycles
+pub const CYCLIC: Properties = 1 << 18;
+/// FST has no cycles
+pub const ACYCLIC: Properties = 1 << 19;
+/// FST has cycles containing the start state
+pub const INITIAL_CYCLIC: Properties = 1 << 20;
+/// FST has no cycles containing the start state
+pub const INITIAL_ACYCLIC: Properties = 1 << 21;
+/// FST is topologically sorted
+pub const TOP_SORTED: Properties = 1 << 22;
+/// FST is not topologically sorted
+pub const NOT_TOP_SORTED: Properties = 1 << 23;
+/// All states reachable from the start state
+pub const ACCESSIBLE: Properties = 1 << 24;
+/// Not all states reachable from the start state
+pub const NOT_ACCESSIBLE: Properties = 1 << 25;
+/// All states can reach a final state
+pub const COACCESSIBLE: Properties = 1 << 26;
+/// Not all states can reach a final state
+pub const NOT_COACCESSIBLE: Properties = 1 << 27;
+
+/// All positive property bits
+pub const POS_PROPERTIES: Properties = ACCEPTOR | I_DETERMINISTIC | O_DETERMINISTIC |
+    EPSILONS | I_EPSILONS | O_EPSILONS | I_LABEL_SORTED | O_LABEL_SORTED | WEIGHTED |
+    CYCLIC | INITIAL_CYCLIC | TOP_SORTED | ACCESSIBLE | COACCESSIBLE;
+/// All negative property bits
+pub const NEG_PROPERTIES: Properties = NOT_ACCEPTOR | NON_I_DETERMINISTIC | NON_O_DETERMINISTIC |
+    NO_EPSILONS | NO_I_EPSILONS | NO_O_EPSILONS | NOT_I_LABEL_SORTED | NOT_O_LABEL_SORTED | UNWEIGHTED |
+    ACYCLIC | INITIAL_ACYCLIC | NOT_TOP_SORTED | NOT_ACCESSIBLE | NOT_COACCESSIBLE;
+/// All property bits
+pub const FST_PROPERTIES: Properties = POS_PROPERTIES | NEG_PROPERTIES;
+
+/// Properties of an empty `Fst`
+pub const NULL_PROPERTIES: Properties = ACCEPTOR | I_DETERMINISTIC | O_DETERMINISTIC |
+    NO_EPSILONS | NO_I_EPSILONS | NO_O_EPSILONS | I_LABEL_SORTED | O_LABEL_SORTED | UNWEIGHTED |
+    ACYCLIC | INITIAL_ACYCLIC | TOP_SORTED | ACCESSIBLE | COACCESSIBLE;
+
+/// Properties that are preserved when arcs are deleted
+pub const DELETE_ARCS_PROPERTIES: Properties = ACCEPTOR | I_DETERMINISTIC | O_DETERMINISTIC |
+    NO_EPSILONS | NO_I_EPSILONS | NO_O_EPSILONS | I_LABEL_SORTED | O_LABEL_SORTED | UNWEIGHTED |
+    ACYCLIC | INITIAL_ACYCLIC | TOP_SORTED | NOT_ACCESSIBLE | NOT_COACCESSIBLE;
+
+/// Properties that depend on the paths through an `Fst` rather than
+/// individual arcs
+const TOPOLOGY_PROPERTIES: Properties = CYCLIC | ACYCLIC | INITIAL_CYCLIC | INITIAL_ACYCLIC |
+    ACCESSIBLE | NOT_ACCESSIBLE | COACCESSIBLE | NOT_COACCESSIBLE;
+
+/// Properties that are preserved when states (and the arcs into
+/// them) are deleted with the remaining states kept in order
+pub const DELETE_STATES_PROPERTIES: Properties = ACCEPTOR | I_DETERMINISTIC | O_DETERMINISTIC |
+    NO_EPSILONS | NO_I_EPSILONS | NO_O_EPSILONS | I_LABEL_SORTED | O_LABEL_SORTED | UNWEIGHTED |
+    ACYCLIC | INITIAL_ACYCLIC | TOP_SORTED;
+
+/// Returns the mask of properties for which either the positive or
+/// negative bit is set in `props`.
+pub fn known_properties(props: Properties) -> Properties {
+    let pos = props & POS_PROPERTIES;
+    let neg = props & NEG_PROPERTIES;
+    pos | (pos << 1) | neg | (neg >> 1)
+}
+
+/// Checks whether all the properties in `mask` are known in `props`.
+pub fn properties_known(props: Properties, mask: Properties) -> bool {
+    known_properties(props) & mask == mask
+}
+
+/// Sets the `prop` bit and clears its complement
+fn set(props: Properties, prop: Properties) -> Properties {
+    let other = if prop & POS_PROPERTIES != 0 {
+        prop << 1
+    } else {
+        prop >> 1
+    };
+    (props | prop) & !other
+}
+
+/// Weights other than `W::zero()` and `W::one()` are non-trivial
+fn is_weighted<W: Weight>(w: &W) -> bool {
+    *w != W::one() && *w != W::zero()
+}
+
+/// Updates the known properties `props` of an `Fst` after adding a
+/// state with the given final weight.
+pub fn add_state_properties<W: Weight>(props: Properties, finalweight: &W) -> Properties {
+    let mut props = props & !(ACCESSIBLE | NOT_ACCESSIBLE | COACCESSIBLE | NOT_COACCESSIBLE);
+    if is_weighted(finalweight) {
+        props = set(props, WEIGHTED);
+    }
+    props
+}
+
+/// Updates the known properties `props` of an `Fst` after setting the
+/// final weight of a state.
+pub fn set_final_properties<W: Weight>(props: Properties, oldweight: &W, finalweight: &W) -> Properties {
+    let mut props = props & !(COACCESSIBLE | NOT_COACCESSIBLE);
+    if is_weighted(oldweight) {
+        props &= !(WEIGHTED | UNWEIGHTED);
+    }
+    if is_weighted(finalweight) {
+        props = set(props, WEIGHTED);
+    }
+    props
+}
+
+/// Updates the known properties `props` of an `Fst` after adding an
+/// arc, where `prevarc` is the last arc (if any) previously leaving
+/// `source`.
+pub fn add_arc_properties<W: Weight, A: Arc<W>>(props: Properties, source: StateId, arc: &A, prevarc: Option<&A>) -> Properties {
+    let mut props = props;
+    if arc.ilabel() != arc.olabel() {
+        props = set(props, NOT_ACCEPTOR);
+    }
+    if arc.ilabel() == 0 {
+        props = set(props, I_EPSILONS);
+        if arc.olabel() == 0 {
+            props = set(props, EPSILONS);
+        }
+    }
+    if arc.olabel() == 0 {
+        props = set(props, O_EPSILONS);
+    }
+    if let Some(prevarc) = prevarc {
+        if arc.ilabel() < prevarc.ilabel() {
+            props = set(props, NOT_I_LABEL_SORTED);
+        }
+        if arc.olabel() < prevarc.olabel() {
+            props = set(props, NOT_O_LABEL_SORTED);
+        }
+        //Determinism is only preserved if labels are strictly increasing
+        if arc.ilabel() == prevarc.ilabel() {
+            props = set(props, NON_I_DETERMINISTIC);
+        } else if props & I_LABEL_SORTED == 0 {
+            props &= !I_DETERMINISTIC;
+        }
+        if arc.olabel() == prevarc.olabel() {
+            props = set(props, NON_O_DETERMINISTIC);
+        } else if props & O_LABEL_SORTED == 0 {
+            props &= !O_DETERMINISTIC;
+        }
+    }
+    if is_weighted(&arc.weight()) {
+        props = set(props, WEIGHTED);
+    }
+    if arc.nextstate() <= source {
+        props = set(props, NOT_TOP_SORTED);
+    }
+    if arc.nextstate() == source {
+        props = set(props, CYCLIC);
+    } else if props & TOP_SORTED == 0 {
+        props &= !ACYCLIC;
+    }
+    if props & INITIAL_CYCLIC == 0 && props & TOP_SORTED == 0 {
+        props &= !INITIAL_ACYCLIC;
+    }
+    //New arcs may only make more states (co)accessible
+    props & !(NOT_ACCESSIBLE | NOT_COACCESSIBLE)
+}
+
+/// Updates the known properties `props` of an `Fst` after changing
+/// an existing arc leaving `source` to `arc`.
+pub fn set_arc_properties<W: Weight, A: Arc<W>>(props: Properties, source: StateId, arc: &A) -> Properties {
+    let props = props & DELETE_ARCS_PROPERTIES &
+        !(I_DETERMINISTIC | O_DETERMINISTIC | I_LABEL_SORTED | O_LABEL_SORTED | NOT_ACCESSIBLE | NOT_COACCESSIBLE);
+    add_arc_properties(props, source, arc, None)
+}
+
+/// Computes the properties in `mask` of `fst`, returning the known
+/// properties which include at least those in `mask`.
+pub fn compute_properties<W: Weight, F: ExpandedFst<W>>(fst: &F, mask: Properties) -> Properties {
+    let nstates = fst.get_numstates();
+    let mask = known_properties(mask);
+    let mut props = NULL_PROPERTIES;
+
+    //Label, weight and determinism properties
+    if mask & !TOPOLOGY_PROPERTIES != 0 {
+        for i in 0..nstates {
+            let mut ilabels = HashSet::<Label>::new();
+            let mut olabels = HashSet::<Label>::new();
+            let mut prev: Option<(Label, Label)> = None;
+            for arc in fst.arc_iter(i) {
+                let (il, ol) = (arc.ilabel(), arc.olabel());
+                if il != ol {
+                    props = set(props, NOT_ACCEPTOR);
+                }
+                if il == 0 {
+                    props = set(props, I_EPSILONS);
+                    if ol == 0 {
+                        props = set(props, EPSILONS);
+                    }
+                }
+                if ol == 0 {
+                    props = set(props, O_EPSILONS);
+                }
+                if let Some((pil, pol)) = prev {
+                    if il < pil {
+                        props = set(props, NOT_I_LABEL_SORTED);
+                    }
+                    if ol < pol {
+                        props = set(props, NOT_O_LABEL_SORTED);
+                    }
+                }
+                if !ilabels.insert(il) {
+                    props = set(props, NON_I_DETERMINISTIC);
+                }
+                if !olabels.insert(ol) {
+                    props = set(props, NON_O_DETERMINISTIC);
+                }
+                if is_weighted(&arc.weight()) {
+                    props = set(props, WEIGHTED);
+                }
+                if arc.nextstate() <= i {
+                    props = set(props, NOT_TOP_SORTED);
+                }
+                prev = Some((il, ol));
+            }
+            if is_weighted(&fst.get_finalweight(i)) {
+                props = set(props, WEIGHTED);
+            }
+        }
+    }
+
+    //Topological properties
+    if mask & TOPOLOGY_PROPERTIES != 0 {
+        let (cyclic, initial_cyclic) = find_cycles(fst);
+        if cyclic {
+            props = set(props, CYCLIC);
+        }
+        if initial_cyclic {
+            props = set(props, INITIAL_CYCLIC);
+        }
+        let (accessible, coaccessible) = find_connected(fst);
+        if accessible.iter().any(|x| !x) {
+            props = set(props, NOT_ACCESSIBLE);
+        }
+        if coaccessible.iter().any(|x| !x) {
+            props = set(props, NOT_COACCESSIBLE);
+        }
+    }
+
+    props & mask
+}
+
+/// Iterative depth-first search returning whether the fst contains any
+/// cycle and whether the start state is on a cycle.
+fn find_cycles<W: Weight, F: ExpandedFst<W>>(fst: &F) -> (bool, bool) {
+    const WHITE: u8 = 0;
+    const GREY: u8 = 1;
+    const BLACK: u8 = 2;
+    let nstates = fst.get_numstates();
+    let mut colour = vec![WHITE; nstates];
+    let mut cyclic = false;
+    let mut initial_cyclic = false;
+    let start = fst.get_start();
+    //Visit from the start state first so that all states reachable
+    //from it are finished before others are visited
+    let roots = start.into_iter().chain((0..nstates).filter(|&s| Some(s) != start));
+    for root in roots {
+        if colour[root] != WHITE {
+            continue
+        }
+        let mut stack: Vec<(StateId, F::Iter)> = vec![(root, fst.arc_iter(root))];
+        colour[root] = GREY;
+        while !stack.is_empty() {
+            let next = stack.last_mut().unwrap().1.next();
+            match next {
+                Some(arc) => {
+                    let ns = arc.nextstate();
+                    if colour[ns] == GREY {
+                        cyclic = true;
+                        if Some(ns) == start {
+                            initial_cyclic = true;
+                        }
+                    } else if colour[ns] == WHITE {
+                        colour[ns] = GREY;
+                        stack.push((ns, fst.arc_iter(ns)));
+                    }
+                },
+                None => {
+                    let (s, _) = stack.pop().unwrap();
+                    colour[s] = BLACK;
+                }
+            }
+        }
+    }
+    (cyclic, initial_cyclic)
+}
+
+/// Returns the accessible and coaccessible states of the fst
+fn find_connected<W: Weight, F: ExpandedFst<W>>(fst: &F) -> (Vec<bool>, Vec<bool>) {
+    let nstates = fst.get_numstates();
+    let mut accessible = vec![false; nstates];
+    let mut coaccessible = vec![false; nstates];
+    let mut incoming: Vec<Vec<StateId>> = vec![Vec::new(); nstates];
+    for i in 0..nstates {
+        for arc in fst.arc_iter(i) {
+            incoming[arc.nextstate()].push(i);
+        }
+    }
+    let mut stack = Vec::new();
+    if let Some(start) = fst.get_start() {
+        accessible[start] = true;
+        stack.push(start);
+    }
+    while let Some(s) = stack.pop() {
+        for arc in fst.arc_iter(s) {
+            let ns = arc.nextstate();
+            if !accessible[ns] {
+                accessible[ns] = true;
+                stack.push(ns);
+            }
+        }
+    }
+    for (i, c) in coaccessible.iter_mut().enumerate() {
+        if fst.is_final(i) {
+            *c = true;
+            stack.push(i);
+        }
+    }
+    while let Some(s) = stack.pop() {
+        for &ps in &incoming[s] {
+            if !coaccessible[ps] {
+                coaccessible[ps] = true;
+                stack.push(ps);
+            }
+        }
+    }
+    (accessible, coaccessible)
+}
@@ -0,0 +1,133 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements the Boolean semiring, for unweighted
+//! automata.
+
+extern crate serde;
+use self::serde::{Serialize, Deserialize};
+
+use super::*;
+
+use std::fmt;
+use std::str::FromStr;
+
+//BOOLEAN SEMIRING: (∨, ∧, false, true)
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BooleanWeight {
+    val: Option<bool>
+}
+
+impl BooleanWeight {
+    pub fn new(val: bool) -> Self {
+        BooleanWeight {val: Some(val)}
+    }
+
+    pub fn value(&self) -> Option<bool> {
+        self.val
+    }
+}
+
+impl Weight for BooleanWeight {
+    fn plus(&self, rhs: &Self) -> Self {
+        match (self.val, rhs.val) {
+            (Some(v1), Some(v2)) => Self::new(v1 || v2),
+            _ => Self::none(),
+        }
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        match (self.val, rhs.val) {
+            (Some(v1), Some(v2)) => Self::new(v1 && v2),
+            _ => Self::none(),
+        }
+    }
+
+    fn zero() -> Self {
+        Self::new(false)
+    }
+
+    fn one() -> Self {
+        Self::new(true)
+    }
+
+    fn none() -> Self {
+        BooleanWeight {val: None}
+    }
+
+    fn is_member(&self) -> bool {
+        self.val.is_some()
+    }
+
+    #[allow(unused_variables)]
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        self.eq(rhs)
+    }
+
+    #[allow(unused_variables)]
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        self.clone()
+    }
+
+    // Division by false is undefined
+    #[allow(unused_variables)]
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        match (self.val, rhs.val) {
+            (Some(_), Some(true)) => self.clone(),
+            _ => Self::none(),
+        }
+    }
+
+    fn reverse(&self) -> Self {
+        self.clone()
+    }
+
+    fn wtype() -> String {
+        String::from("boolean")
+    }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match s {
+            "1" | "true" => Ok(Self::new(true)),
+            "0" | "false" => Ok(Self::new(false)),
+            "None" => Ok(Self::none()),
+            _ => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
+}
+
+impl fmt::Display for BooleanWeight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.val {
+            Some(true) => write!(f, "1"),
+            Some(false) => write!(f, "0"),
+            None => write!(f, "None"),
+        }
+    }
+}
+
+impl FromStr for BooleanWeight {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl LeftSemiring for BooleanWeight {}
+impl RightSemiring for BooleanWeight {}
+impl Semiring for BooleanWeight {}
+impl Commutative for BooleanWeight {}
+impl Idempotent for BooleanWeight {}
+impl Path for BooleanWeight {}
+
+impl NaturalLess for BooleanWeight {
+    fn natural_less(&self, rhs: &Self) -> bool {
+        self.plus(rhs).eq(rhs) && !self.eq(rhs)
+    }
+}
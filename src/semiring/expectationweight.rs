@@ -0,0 +1,163 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+// This file contains portions of code ported from OpenFst
+// (http://www.openfst.org) under the following licence and
+// attribution:
+//
+// """
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Copyright 2005-2010 Google, Inc.
+// Author: riley@google.com (Michael Riley)
+// """
+////////////////////////////////////////////////////////////////////////////////
+
+//! This module implements the expectation semiring, which pairs a
+//! probability-like weight `w` with an accumulated value `v` so that
+//! the shortest distance of an FST with arc weights `(p, p·x)` yields
+//! `(Σ p, Σ p·x)` over all paths, i.e. unnormalised expectations. See:
+//!
+//! Jason Eisner. "Expectation semirings: Flexible EM for
+//! finite-state transducers," In: *Proceedings of the ESSLLI Workshop
+//! on Finite-State Methods in NLP* (FSMNLP), 2001.
+
+extern crate serde;
+use self::serde::{Serialize, Deserialize};
+
+use super::*;
+use super::float::Float;
+use super::floatweight::{LogWeight, SignedLogWeight};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A Weight type `V` which can be scaled (multiplied) by a weight of
+/// type `W`, i.e. a module over the semiring `W`.
+pub trait Module<W: Weight>: Weight {
+    fn scale(&self, w: &W) -> Self;
+}
+
+impl<W: Weight> Module<W> for W {
+    fn scale(&self, w: &W) -> Self {
+        w.times(self)
+    }
+}
+
+/// Allows values which may be negative, e.g. `ExpectationWeight<LogWeight<T>, SignedLogWeight<T>>`
+impl<T: Float<T>> Module<LogWeight<T>> for SignedLogWeight<T> {
+    fn scale(&self, w: &LogWeight<T>) -> Self {
+        SignedLogWeight::from_log(false, w.clone()).times(self)
+    }
+}
+
+//EXPECTATION SEMIRING: ((w1,v1) ⊕ (w2,v2) = (w1 ⊕ w2, v1 ⊕ v2),
+//                      (w1,v1) ⊗ (w2,v2) = (w1 ⊗ w2, w1·v2 ⊕ w2·v1), (0,0), (1,0))
+#[derive(Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
+pub struct ExpectationWeight<W: Weight, V: Module<W>> {
+    w: W,
+    v: V
+}
+
+impl<W: Weight, V: Module<W>> ExpectationWeight<W, V> {
+    pub fn new(w: W, v: V) -> Self {
+        ExpectationWeight {w, v}
+    }
+
+    pub fn value1(&self) -> &W {
+        &self.w
+    }
+
+    pub fn value2(&self) -> &V {
+        &self.v
+    }
+}
+
+impl<W: Weight, V: Module<W>> Weight for ExpectationWeight<W, V> {
+    fn plus(&self, rhs: &Self) -> Self {
+        Self::new(self.w.plus(&rhs.w), self.v.plus(&rhs.v))
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        Self::new(self.w.times(&rhs.w), rhs.v.scale(&self.w).plus(&self.v.scale(&rhs.w)))
+    }
+
+    fn zero() -> Self {
+        Self::new(W::zero(), V::zero())
+    }
+
+    fn one() -> Self {
+        Self::new(W::one(), V::zero())
+    }
+
+    fn none() -> Self {
+        Self::new(W::none(), V::none())
+    }
+
+    fn is_member(&self) -> bool {
+        self.w.is_member() && self.v.is_member()
+    }
+
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        self.w.approx_eq(&rhs.w, delta) && self.v.approx_eq(&rhs.v, delta)
+    }
+
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        Self::new(self.w.quantize(delta), self.v.quantize(delta))
+    }
+
+    // Not defined in general (requires subtraction of values)
+    #[allow(unused_variables)]
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        Self::none()
+    }
+
+    fn reverse(&self) -> Self {
+        Self::new(self.w.reverse(), self.v.reverse())
+    }
+
+    fn wtype() -> String {
+        format!("expectation_{}_{}", W::wtype(), V::wtype())
+    }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match parse_pair(s, ',', |t| W::parse_weight(t).ok(), |t| V::parse_weight(t).ok()) {
+            Some((w, v)) => Ok(Self::new(w, v)),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
+}
+
+impl<W: Weight + fmt::Display, V: Module<W> + fmt::Display> fmt::Display for ExpectationWeight<W, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.w, self.v)
+    }
+}
+
+impl<W: Weight, V: Module<W>> FromStr for ExpectationWeight<W, V> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl<W: Weight + LeftSemiring, V: Module<W>> LeftSemiring for ExpectationWeight<W, V> {}
+impl<W: Weight + RightSemiring, V: Module<W>> RightSemiring for ExpectationWeight<W, V> {}
+impl<W: Weight + Semiring, V: Module<W>> Semiring for ExpectationWeight<W, V> {}
+impl<W: Weight + Commutative, V: Module<W>> Commutative for ExpectationWeight<W, V> {}
@@ -10,23 +10,28 @@
 //! for use in Weight. Not generally used directly.
 
 use std::fmt::{Debug};
-use std::ops::{Add, Sub};
+use std::ops::{Add, Sub, Mul, Div};
+use std::str::FromStr;
 use std::{f64, f32};
 
 // Used to implement `approx_eq()` and `quantize()`
 const DEFAULT_DELTA: f32 = 1.0 / 1024.0;
 
 // Internal float trait for our implementations over either f32 or f64
-pub trait Float<T>: Debug + Clone + PartialOrd + Add<Output=T> + Sub<Output=T> {
+pub trait Float<T>: Debug + Clone + PartialOrd + Add<Output=T> + Sub<Output=T> + Mul<Output=T> + Div<Output=T> + FromStr {
     fn zero() -> T;
     fn one() -> T;
     fn nan() -> T;
     fn infty() -> T;
     fn neg_infty() -> T;
     fn logexp(self) -> T;
+    fn lognegexp(self) -> T;
     fn approx_eq(self, rhs: T, delta: Option<f32>) -> bool;
     fn quantize(self, delta: Option<f32>) -> T;
+    fn quantized_bits(self) -> u64;     // used to hash weights
     fn from_u32(u32) -> T;
+    fn from_f64(v: f64) -> T;
+    fn to_f64(self) -> f64;
     fn get_precision() -> &'static str;
 }
 
@@ -41,6 +46,10 @@ impl Float<f64> for f64 {
         (1.0 + (-self).exp()).ln()
     }
 
+    fn lognegexp(self) -> f64 {
+        (-(-self).exp()).ln_1p()
+    }
+
     fn approx_eq(self, rhs: f64, delta: Option<f32>) -> bool {
         let d = if let Some(d) = delta {
             d as f64
@@ -66,10 +75,27 @@ impl Float<f64> for f64 {
         }
     }
 
+    fn quantized_bits(self) -> u64 {
+        let q = self.quantize(None);
+        if q == 0.0 {
+            0    //-0.0 == 0.0
+        } else {
+            q.to_bits()
+        }
+    }
+
     fn from_u32(i: u32) -> f64 {
         i as f64
     }
 
+    fn from_f64(v: f64) -> f64 {
+        v
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
     fn get_precision() -> &'static str {
         "64"
     }
@@ -86,6 +112,10 @@ impl Float<f32> for f32 {
         (1.0 + (-self).exp()).ln()
     }
 
+    fn lognegexp(self) -> f32 {
+        (-(-self).exp()).ln_1p()
+    }
+
     fn approx_eq(self, rhs: f32, delta: Option<f32>) -> bool {
         let d = if let Some(d) = delta {
             d
@@ -111,10 +141,27 @@ impl Float<f32> for f32 {
         }
     }
 
+    fn quantized_bits(self) -> u64 {
+        let q = self.quantize(None);
+        if q == 0.0 {
+            0    //-0.0 == 0.0
+        } else {
+            q.to_bits() as u64
+        }
+    }
+
     fn from_u32(i: u32) -> f32 {
         i as f32
     }
 
+    fn from_f64(v: f64) -> f32 {
+        v as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
     fn get_precision() -> &'static str {
         "32"
     }
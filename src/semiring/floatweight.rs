@@ -39,13 +39,55 @@ use super::*;
 use super::float::Float;
 
 use std::fmt;
+use std::str::FromStr;
+use std::hash::{Hash, Hasher};
 
 pub trait FloatWeight<T: Float<T>>: Weight {
     fn new(val: Option<T>) -> Self;
 }
 
+/// Float weights whose values can be copied to weights of type `W`
+/// with the same meaning: `TropicalWeight` and `LogWeight` (both
+/// negative log probabilities) to each other, and each float weight
+/// type to itself, at any precision.
+pub trait ConvertFloatWeight<W: Weight>: Weight {
+    fn convert_value(&self) -> W;
+}
+
+macro_rules! impl_convert_float_weight {
+    ($from:ident => $($to:ident),+) => {
+        $(impl<T1: Float<T1>, T2: Float<T2>> ConvertFloatWeight<$to<T2>> for $from<T1> {
+            fn convert_value(&self) -> $to<T2> {
+                $to::new(self.val.clone().map(|v| T2::from_f64(v.to_f64())))
+            }
+        })+
+    };
+}
+
+impl_convert_float_weight!(TropicalWeight => TropicalWeight, LogWeight);
+impl_convert_float_weight!(LogWeight => LogWeight, TropicalWeight);
+impl_convert_float_weight!(MinmaxWeight => MinmaxWeight);
+impl_convert_float_weight!(RealWeight => RealWeight);
+impl_convert_float_weight!(ArcticWeight => ArcticWeight);
+
+impl<T1: Float<T1>, T2: Float<T2>> ConvertFloatWeight<SignedLogWeight<T2>> for SignedLogWeight<T1> {
+    fn convert_value(&self) -> SignedLogWeight<T2> {
+        SignedLogWeight {negative: self.negative, mag: self.mag.convert_value()}
+    }
+}
+
+// Reads the value of a float weight as written by `Display`: either a
+// float (including `inf`/`Infinity` and `-inf`/`-Infinity`) or `None`
+fn parse_float<T: Float<T>>(s: &str) -> Option<Option<T>> {
+    if s == "None" {
+        Some(None)
+    } else {
+        s.parse().ok().map(Some)
+    }
+}
+
 //TROPICAL SEMIRING: (min, +, inf, 0)
-#[derive(Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TropicalWeight<T: Float<T>> {
     val: Option<T>
 }
@@ -54,6 +96,10 @@ impl<T: Float<T>> TropicalWeight<T> {
     pub fn new(val: Option<T>) -> Self {
         TropicalWeight {val: val}
     }
+
+    pub fn value(&self) -> Option<T> {
+        self.val.clone()
+    }
 }
 
 impl<T: Float<T>> FloatWeight<T> for TropicalWeight<T> {
@@ -145,6 +191,13 @@ impl<T: Float<T>> Weight for TropicalWeight<T> {
     fn wtype() -> String {
         format!("tropical{}", T::get_precision())
     }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match parse_float(s) {
+            Some(val) => Ok(Self::new(val)),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
 }
 
 impl<T: Float<T> + fmt::Display> fmt::Display for TropicalWeight<T> {
@@ -171,6 +224,20 @@ impl<T: Float<T>> PartialEq for TropicalWeight<T> {
     }
 }
 
+impl<T: Float<T>> Hash for TropicalWeight<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.val.clone().map(|v| v.quantized_bits()).hash(state);
+    }
+}
+
+impl<T: Float<T>> FromStr for TropicalWeight<T> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
 impl<T: Float<T>> LeftSemiring for TropicalWeight<T> {}
 impl<T: Float<T>> RightSemiring for TropicalWeight<T> {}
 impl<T: Float<T>> Semiring for TropicalWeight<T> {}
@@ -186,7 +253,7 @@ impl<T: Float<T>> NaturalLess for TropicalWeight<T> {
 
 ////////////////////////////////////////////////////////////////////////////////
 //LOG SEMIRING: (ln(e^-x + e^y), +, inf, 0)
-#[derive(Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LogWeight<T: Float<T>> {
     val: Option<T>
 }
@@ -195,6 +262,10 @@ impl<T: Float<T>> LogWeight<T> {
     pub fn new(val: Option<T>) -> Self {
         LogWeight {val: val}
     }
+
+    pub fn value(&self) -> Option<T> {
+        self.val.clone()
+    }
 }
 
 impl<T: Float<T>> FloatWeight<T> for LogWeight<T> {
@@ -300,6 +371,13 @@ impl<T: Float<T>> Weight for LogWeight<T> {
     fn wtype() -> String {
         format!("log{}", T::get_precision())
     }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match parse_float(s) {
+            Some(val) => Ok(Self::new(val)),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
 }
 
 impl<T: Float<T> + fmt::Display> fmt::Display for LogWeight<T> {
@@ -326,6 +404,20 @@ impl<T: Float<T>> PartialEq for LogWeight<T> {
     }
 }
 
+impl<T: Float<T>> Hash for LogWeight<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.val.clone().map(|v| v.quantized_bits()).hash(state);
+    }
+}
+
+impl<T: Float<T>> FromStr for LogWeight<T> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
 impl<T: Float<T>> LeftSemiring for LogWeight<T> {}
 impl<T: Float<T>> RightSemiring for LogWeight<T> {}
 impl<T: Float<T>> Semiring for LogWeight<T> {}
@@ -333,7 +425,7 @@ impl<T: Float<T>> Commutative for LogWeight<T> {}
 
 ////////////////////////////////////////////////////////////////////////////////
 //MINMAX SEMIRING: (min, max, inf, -inf)
-#[derive(Clone, Debug, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MinmaxWeight<T: Float<T>> {
     val: Option<T>
 }
@@ -342,6 +434,10 @@ impl<T: Float<T>> MinmaxWeight<T> {
     pub fn new(val: Option<T>) -> Self {
         MinmaxWeight {val: val}
     }
+
+    pub fn value(&self) -> Option<T> {
+        self.val.clone()
+    }
 }
 
 impl<T: Float<T>> FloatWeight<T> for MinmaxWeight<T> {
@@ -431,6 +527,13 @@ impl<T: Float<T>> Weight for MinmaxWeight<T> {
     fn wtype() -> String {
         format!("minmax{}", T::get_precision())
     }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match parse_float(s) {
+            Some(val) => Ok(Self::new(val)),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
 }
 
 impl<T: Float<T> + fmt::Display> fmt::Display for MinmaxWeight<T> {
@@ -457,6 +560,20 @@ impl<T: Float<T>> PartialEq for MinmaxWeight<T> {
     }
 }
 
+impl<T: Float<T>> Hash for MinmaxWeight<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.val.clone().map(|v| v.quantized_bits()).hash(state);
+    }
+}
+
+impl<T: Float<T>> FromStr for MinmaxWeight<T> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
 impl<T: Float<T>> LeftSemiring for MinmaxWeight<T> {}
 impl<T: Float<T>> RightSemiring for MinmaxWeight<T> {}
 impl<T: Float<T>> Semiring for MinmaxWeight<T> {}
@@ -469,3 +586,498 @@ impl<T: Float<T>> NaturalLess for MinmaxWeight<T> {
         self.plus(rhs).eq(rhs) && !self.eq(rhs)
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+//REAL SEMIRING: (+, *, 0, 1)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RealWeight<T: Float<T>> {
+    val: Option<T>
+}
+
+impl<T: Float<T>> RealWeight<T> {
+    pub fn new(val: Option<T>) -> Self {
+        RealWeight {val}
+    }
+
+    pub fn value(&self) -> Option<T> {
+        self.val.clone()
+    }
+}
+
+impl<T: Float<T>> FloatWeight<T> for RealWeight<T> {
+    fn new(val: Option<T>) -> Self {
+        RealWeight::new(val)
+    }
+}
+
+impl<T: Float<T>> Weight for RealWeight<T> {
+    fn plus(&self, rhs: &Self) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            Self::new(None)
+        } else {
+            let (v1, v2) = (self.val.clone().unwrap(), rhs.val.clone().unwrap());
+            Self::new(Some(v1 + v2))
+        }
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            Self::new(None)
+        } else {
+            let (v1, v2) = (self.val.clone().unwrap(), rhs.val.clone().unwrap());
+            Self::new(Some(v1 * v2))
+        }
+    }
+
+    fn zero() -> Self {
+        Self::new(Some(T::zero()))
+    }
+
+    fn one() -> Self {
+        Self::new(Some(T::one()))
+    }
+
+    fn none() -> Self {
+        Self::new(None)
+    }
+
+    fn is_member(&self) -> bool {
+        if let Some(val) = self.val.clone() {
+            !(val == T::nan() || val == T::infty() || val == T::neg_infty())
+        } else {
+            false
+        }
+    }
+
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        if let Some(val) = self.val.clone() {
+            if let Some(val2) = rhs.val.clone() {
+                val.approx_eq(val2, delta)
+            } else {
+                false
+            }
+        } else {
+            false
+        }
+    }
+
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        if let Some(val) = self.val.clone() {
+            Self::new(Some(val.quantize(delta)))
+        } else {
+            Self::new(None)
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            Self::new(None)
+        } else {
+            let (v1, v2) = (self.val.clone().unwrap(), rhs.val.clone().unwrap());
+            if v2 == T::zero() {
+                Self::new(None)
+            } else {
+                Self::new(Some(v1 / v2))
+            }
+        }
+    }
+
+    fn reverse(&self) -> Self {
+        self.clone()
+    }
+
+    fn wtype() -> String {
+        format!("real{}", T::get_precision())
+    }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match parse_float(s) {
+            Some(val) => Ok(Self::new(val)),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
+}
+
+impl<T: Float<T> + fmt::Display> fmt::Display for RealWeight<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref val) = self.val {
+            write!(f, "{}", val)
+        } else {
+            write!(f, "None")
+        }
+    }
+}
+
+impl<T: Float<T>> PartialEq for RealWeight<T> {
+    fn eq(&self, rhs: &Self) -> bool {
+        if let Some(val) = self.val.clone() {
+            if let Some(val2) = rhs.val.clone() {
+                val == val2
+            } else {
+                false
+            }
+        } else {
+            false
+        }
+    }
+}
+
+impl<T: Float<T>> Hash for RealWeight<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.val.clone().map(|v| v.quantized_bits()).hash(state);
+    }
+}
+
+impl<T: Float<T>> FromStr for RealWeight<T> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl<T: Float<T>> LeftSemiring for RealWeight<T> {}
+impl<T: Float<T>> RightSemiring for RealWeight<T> {}
+impl<T: Float<T>> Semiring for RealWeight<T> {}
+impl<T: Float<T>> Commutative for RealWeight<T> {}
+
+////////////////////////////////////////////////////////////////////////////////
+//ARCTIC SEMIRING: (max, +, -inf, 0)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArcticWeight<T: Float<T>> {
+    val: Option<T>
+}
+
+impl<T: Float<T>> ArcticWeight<T> {
+    pub fn new(val: Option<T>) -> Self {
+        ArcticWeight {val}
+    }
+
+    pub fn value(&self) -> Option<T> {
+        self.val.clone()
+    }
+}
+
+impl<T: Float<T>> FloatWeight<T> for ArcticWeight<T> {
+    fn new(val: Option<T>) -> Self {
+        ArcticWeight::new(val)
+    }
+}
+
+impl<T: Float<T>> Weight for ArcticWeight<T> {
+    fn plus(&self, rhs: &Self) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            Self::new(None)
+        } else if self.val > rhs.val {
+            self.clone()
+        } else {
+            rhs.clone()
+        }
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            Self::new(None)
+        } else {
+            let (v1, v2) = (self.val.clone().unwrap(), rhs.val.clone().unwrap());
+            Self::new(Some(v1 + v2))
+        }
+    }
+
+    fn zero() -> Self {
+        Self::new(Some(T::neg_infty()))
+    }
+
+    fn one() -> Self {
+        Self::new(Some(T::zero()))
+    }
+
+    fn none() -> Self {
+        Self::new(None)
+    }
+
+    fn is_member(&self) -> bool {
+        if let Some(val) = self.val.clone() {
+            !(val == T::nan() || val == T::infty())
+        } else {
+            false
+        }
+    }
+
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        if let Some(val) = self.val.clone() {
+            if let Some(val2) = rhs.val.clone() {
+                val.approx_eq(val2, delta)
+            } else {
+                false
+            }
+        } else {
+            false
+        }
+    }
+
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        if let Some(val) = self.val.clone() {
+            Self::new(Some(val.quantize(delta)))
+        } else {
+            Self::new(None)
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            Self::new(None)
+        } else {
+            let (v1, v2) = (self.val.clone().unwrap(), rhs.val.clone().unwrap());
+            if v2 == T::neg_infty() {
+                Self::new(None)
+            } else if v1 == T::neg_infty() {
+                self.clone()
+            } else {
+                Self::new(Some(v1 - v2))
+            }
+        }
+    }
+
+    fn reverse(&self) -> Self {
+        self.clone()
+    }
+
+    fn wtype() -> String {
+        format!("arctic{}", T::get_precision())
+    }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match parse_float(s) {
+            Some(val) => Ok(Self::new(val)),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
+}
+
+impl<T: Float<T> + fmt::Display> fmt::Display for ArcticWeight<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref val) = self.val {
+            write!(f, "{}", val)
+        } else {
+            write!(f, "None")
+        }
+    }
+}
+
+impl<T: Float<T>> PartialEq for ArcticWeight<T> {
+    fn eq(&self, rhs: &Self) -> bool {
+        if let Some(val) = self.val.clone() {
+            if let Some(val2) = rhs.val.clone() {
+                val == val2
+            } else {
+                false
+            }
+        } else {
+            false
+        }
+    }
+}
+
+impl<T: Float<T>> Hash for ArcticWeight<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.val.clone().map(|v| v.quantized_bits()).hash(state);
+    }
+}
+
+impl<T: Float<T>> FromStr for ArcticWeight<T> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl<T: Float<T>> LeftSemiring for ArcticWeight<T> {}
+impl<T: Float<T>> RightSemiring for ArcticWeight<T> {}
+impl<T: Float<T>> Semiring for ArcticWeight<T> {}
+impl<T: Float<T>> Commutative for ArcticWeight<T> {}
+impl<T: Float<T>> Idempotent for ArcticWeight<T> {}
+impl<T: Float<T>> Path for ArcticWeight<T> {}
+
+impl<T: Float<T>> NaturalLess for ArcticWeight<T> {
+    fn natural_less(&self, rhs: &Self) -> bool {
+        self.plus(rhs).eq(rhs) && !self.eq(rhs)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//SIGNED LOG SEMIRING: (signed ln(e^-x + e^y), +, inf, 0) with a sign
+//bit and `LogWeight` magnitude, representing negative real values in
+//log space
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedLogWeight<T: Float<T>> {
+    negative: bool,
+    mag: LogWeight<T>
+}
+
+impl<T: Float<T>> SignedLogWeight<T> {
+    pub fn new(negative: bool, val: Option<T>) -> Self {
+        SignedLogWeight {negative, mag: LogWeight::new(val)}
+    }
+
+    pub fn from_log(negative: bool, mag: LogWeight<T>) -> Self {
+        SignedLogWeight {negative, mag}
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn magnitude(&self) -> &LogWeight<T> {
+        &self.mag
+    }
+
+    fn is_zero(&self) -> bool {
+        self.mag == LogWeight::zero()
+    }
+}
+
+impl<T: Float<T>> FloatWeight<T> for SignedLogWeight<T> {
+    fn new(val: Option<T>) -> Self {
+        SignedLogWeight::new(false, val)
+    }
+}
+
+impl<T: Float<T>> Weight for SignedLogWeight<T> {
+
+    fn plus(&self, rhs: &Self) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            Self::none()
+        } else if self.is_zero() {
+            rhs.clone()
+        } else if rhs.is_zero() {
+            self.clone()
+        } else if self.negative == rhs.negative {
+            SignedLogWeight {negative: self.negative, mag: self.mag.plus(&rhs.mag)}
+        } else {
+            //subtract the smaller magnitude from the larger
+            let (v1, v2) = (self.mag.val.clone().unwrap(), rhs.mag.val.clone().unwrap());
+            if v1 == v2 {
+                Self::zero()
+            } else if v1 < v2 {
+                Self::new(self.negative, Some(v1.clone() - (v2 - v1).lognegexp()))
+            } else {
+                Self::new(rhs.negative, Some(v2.clone() - (v1 - v2).lognegexp()))
+            }
+        }
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            Self::none()
+        } else if self.is_zero() || rhs.is_zero() {
+            Self::zero()
+        } else {
+            SignedLogWeight {negative: self.negative != rhs.negative, mag: self.mag.times(&rhs.mag)}
+        }
+    }
+
+    fn zero() -> Self {
+        SignedLogWeight {negative: false, mag: LogWeight::zero()}
+    }
+
+    fn one() -> Self {
+        SignedLogWeight {negative: false, mag: LogWeight::one()}
+    }
+
+    fn none() -> Self {
+        SignedLogWeight {negative: false, mag: LogWeight::none()}
+    }
+
+    fn is_member(&self) -> bool {
+        self.mag.is_member()
+    }
+
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        if self.negative == rhs.negative {
+            self.mag.approx_eq(&rhs.mag, delta)
+        } else {
+            self.is_zero() && rhs.is_zero()
+        }
+    }
+
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        SignedLogWeight {negative: self.negative, mag: self.mag.quantize(delta)}
+    }
+
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) || rhs.is_zero() {
+            Self::none()
+        } else if self.is_zero() {
+            Self::zero()
+        } else {
+            SignedLogWeight {negative: self.negative != rhs.negative, mag: self.mag.divide(&rhs.mag, divtype)}
+        }
+    }
+
+    fn reverse(&self) -> Self {
+        self.clone()
+    }
+
+    fn wtype() -> String {
+        format!("signedlog{}", T::get_precision())
+    }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        let mut fields = s.splitn(2, ',');
+        let negative = match fields.next() {
+            Some("1") => false,
+            Some("-1") => true,
+            _ => return Err(ParseWeightError::new(&Self::wtype(), s))
+        };
+        match fields.next().and_then(parse_float) {
+            Some(val) => Ok(Self::new(negative, val)),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
+}
+
+impl<T: Float<T> + fmt::Display> fmt::Display for SignedLogWeight<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative {
+            write!(f, "-1,{}", self.mag)
+        } else {
+            write!(f, "1,{}", self.mag)
+        }
+    }
+}
+
+// The sign of zero is ignored
+impl<T: Float<T>> PartialEq for SignedLogWeight<T> {
+    fn eq(&self, rhs: &Self) -> bool {
+        if self.is_zero() && rhs.is_zero() {
+            true
+        } else {
+            self.negative == rhs.negative && self.mag == rhs.mag
+        }
+    }
+}
+
+// Consistent with `eq()` which ignores the sign of zero
+impl<T: Float<T>> Hash for SignedLogWeight<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.negative && !self.is_zero()).hash(state);
+        self.mag.hash(state);
+    }
+}
+
+impl<T: Float<T>> FromStr for SignedLogWeight<T> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl<T: Float<T>> LeftSemiring for SignedLogWeight<T> {}
+impl<T: Float<T>> RightSemiring for SignedLogWeight<T> {}
+impl<T: Float<T>> Semiring for SignedLogWeight<T> {}
+impl<T: Float<T>> Commutative for SignedLogWeight<T> {}
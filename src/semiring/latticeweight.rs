@@ -0,0 +1,201 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements the weight of speech recognition lattices as
+//! used by the Kaldi toolkit (http://kaldi-asr.org): a pair of costs
+//! (negated log scores) for the graph (language model, pronunciation
+//! and transition) and acoustic model, which are kept separate so that
+//! they can be rescaled after decoding.
+//!
+//! ⊕ selects the pair with the lower total cost (ties broken by the
+//! lower graph cost), so that this is a path semiring equivalent to
+//! the tropical semiring over the total cost.
+
+extern crate serde;
+use self::serde::{Serialize, Deserialize};
+
+use super::*;
+use super::float::Float;
+
+use std::fmt;
+use std::str::FromStr;
+use std::hash::{Hash, Hasher};
+
+//LATTICE SEMIRING: (argmin over total cost, component-wise +, (inf, inf), (0, 0))
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LatticeWeight<T: Float<T>> {
+    val: Option<(T, T)>
+}
+
+impl<T: Float<T>> LatticeWeight<T> {
+    pub fn new(val: Option<(T, T)>) -> Self {
+        LatticeWeight {val}
+    }
+
+    pub fn from_costs(graph: T, acoustic: T) -> Self {
+        Self::new(Some((graph, acoustic)))
+    }
+
+    /// The graph and acoustic costs
+    pub fn value(&self) -> Option<(T, T)> {
+        self.val.clone()
+    }
+
+    pub fn graph_cost(&self) -> Option<T> {
+        self.val.clone().map(|(g, _)| g)
+    }
+
+    pub fn acoustic_cost(&self) -> Option<T> {
+        self.val.clone().map(|(_, a)| a)
+    }
+
+    /// The combined cost `lm_scale * graph + acoustic_scale * acoustic`
+    pub fn scaled_cost(&self, lm_scale: T, acoustic_scale: T) -> Option<T> {
+        self.val.clone().map(|(g, a)| {
+            // Avoid 0 * inf for zero weights
+            if g == T::infty() || a == T::infty() {
+                T::infty()
+            } else {
+                lm_scale * g + acoustic_scale * a
+            }
+        })
+    }
+}
+
+impl<T: Float<T>> Weight for LatticeWeight<T> {
+    fn plus(&self, rhs: &Self) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            return Self::none()
+        }
+        let ((g1, a1), (g2, a2)) = (self.val.clone().unwrap(), rhs.val.clone().unwrap());
+        let (c1, c2) = (g1.clone() + a1, g2.clone() + a2);
+        if c1 < c2 || (c1 == c2 && g1 <= g2) {
+            self.clone()
+        } else {
+            rhs.clone()
+        }
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            Self::none()
+        } else {
+            let ((g1, a1), (g2, a2)) = (self.val.clone().unwrap(), rhs.val.clone().unwrap());
+            Self::from_costs(g1 + g2, a1 + a2)
+        }
+    }
+
+    fn zero() -> Self {
+        Self::from_costs(T::infty(), T::infty())
+    }
+
+    fn one() -> Self {
+        Self::from_costs(T::zero(), T::zero())
+    }
+
+    fn none() -> Self {
+        Self::new(None)
+    }
+
+    fn is_member(&self) -> bool {
+        if let Some((g, a)) = self.val.clone() {
+            !(g == T::nan() || g == T::neg_infty() || a == T::nan() || a == T::neg_infty())
+        } else {
+            false
+        }
+    }
+
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        match (self.val.clone(), rhs.val.clone()) {
+            (Some((g1, a1)), Some((g2, a2))) => g1.approx_eq(g2, delta) && a1.approx_eq(a2, delta),
+            _ => false,
+        }
+    }
+
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        Self::new(self.val.clone().map(|(g, a)| (g.quantize(delta), a.quantize(delta))))
+    }
+
+    #[allow(unused_variables)]
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) || *rhs == Self::zero() {
+            Self::none()
+        } else if *self == Self::zero() {
+            self.clone()
+        } else {
+            let ((g1, a1), (g2, a2)) = (self.val.clone().unwrap(), rhs.val.clone().unwrap());
+            let w = Self::from_costs(g1 - g2, a1 - a2);
+            if w.is_member() { w } else { Self::none() }
+        }
+    }
+
+    fn reverse(&self) -> Self {
+        self.clone()
+    }
+
+    fn wtype() -> String {
+        format!("lattice{}", T::get_precision())
+    }
+
+    // Written as `graph,acoustic` (as in Kaldi)
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        if s == "None" {
+            return Ok(Self::none())
+        }
+        match parse_pair(s, ',', |t| t.parse::<T>().ok(), |t| t.parse::<T>().ok()) {
+            Some(val) => Ok(Self::new(Some(val))),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
+}
+
+impl<T: Float<T> + fmt::Display> fmt::Display for LatticeWeight<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((ref g, ref a)) = self.val {
+            write!(f, "{},{}", g, a)
+        } else {
+            write!(f, "None")
+        }
+    }
+}
+
+impl<T: Float<T>> PartialEq for LatticeWeight<T> {
+    fn eq(&self, rhs: &Self) -> bool {
+        match (self.val.clone(), rhs.val.clone()) {
+            (Some(v1), Some(v2)) => v1 == v2,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Float<T>> Hash for LatticeWeight<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.val.clone().map(|(g, a)| (g.quantized_bits(), a.quantized_bits())).hash(state);
+    }
+}
+
+impl<T: Float<T>> FromStr for LatticeWeight<T> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl<T: Float<T>> LeftSemiring for LatticeWeight<T> {}
+impl<T: Float<T>> RightSemiring for LatticeWeight<T> {}
+impl<T: Float<T>> Semiring for LatticeWeight<T> {}
+impl<T: Float<T>> Commutative for LatticeWeight<T> {}
+impl<T: Float<T>> Idempotent for LatticeWeight<T> {}
+impl<T: Float<T>> Path for LatticeWeight<T> {}
+
+impl<T: Float<T>> NaturalLess for LatticeWeight<T> {
+    fn natural_less(&self, rhs: &Self) -> bool {
+        self.plus(rhs).eq(rhs) && !self.eq(rhs)
+    }
+}
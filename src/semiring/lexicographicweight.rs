@@ -0,0 +1,172 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+// This file contains portions of code ported from OpenFst
+// (http://www.openfst.org) under the following licence and
+// attribution:
+//
+// """
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Copyright 2005-2010 Google, Inc.
+// Author: riley@google.com (Michael Riley)
+// """
+////////////////////////////////////////////////////////////////////////////////
+
+//! This module implements the lexicographic product of two Weight
+//! types with the path property: paths are ranked by the first
+//! component and ties are broken by the second. See the source file
+//! `test_semiring.rs` for simple examples of intended use.
+
+extern crate serde;
+use self::serde::{Serialize, Deserialize};
+
+use super::*;
+
+use std::fmt;
+use std::str::FromStr;
+
+// Returns the `Path` choice of `a ⊕ b`: `Some(true)` if `a`,
+// `Some(false)` if `b` and `None` if they are equal.
+fn choose<W: Weight>(a: &W, b: &W) -> Option<bool> {
+    let p = a.plus(b);
+    if p.eq(a) && !p.eq(b) {
+        Some(true)
+    } else if p.eq(b) && !p.eq(a) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+//LEXICOGRAPHIC SEMIRING: (W1 x W2, lex-min, ⊗1 x ⊗2, (0,0), (1,1))
+#[derive(Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
+pub struct LexicographicWeight<W1: Weight + Path, W2: Weight + Path> {
+    w1: W1,
+    w2: W2
+}
+
+impl<W1: Weight + Path, W2: Weight + Path> LexicographicWeight<W1, W2> {
+    pub fn new(w1: W1, w2: W2) -> Self {
+        LexicographicWeight {w1, w2}
+    }
+
+    pub fn value1(&self) -> &W1 {
+        &self.w1
+    }
+
+    pub fn value2(&self) -> &W2 {
+        &self.w2
+    }
+}
+
+impl<W1: Weight + Path, W2: Weight + Path> Weight for LexicographicWeight<W1, W2> {
+    fn plus(&self, rhs: &Self) -> Self {
+        if (!self.is_member()) || (!rhs.is_member()) {
+            Self::none()
+        } else {
+            match choose(&self.w1, &rhs.w1) {
+                Some(true) => self.clone(),
+                Some(false) => rhs.clone(),
+                None => match choose(&self.w2, &rhs.w2) {
+                    Some(false) => rhs.clone(),
+                    _ => self.clone(),
+                }
+            }
+        }
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        Self::new(self.w1.times(&rhs.w1), self.w2.times(&rhs.w2))
+    }
+
+    fn zero() -> Self {
+        Self::new(W1::zero(), W2::zero())
+    }
+
+    fn one() -> Self {
+        Self::new(W1::one(), W2::one())
+    }
+
+    fn none() -> Self {
+        Self::new(W1::none(), W2::none())
+    }
+
+    // Zero and non-zero components cannot be mixed
+    fn is_member(&self) -> bool {
+        if !self.w1.is_member() || !self.w2.is_member() {
+            false
+        } else {
+            (self.w1 == W1::zero()) == (self.w2 == W2::zero())
+        }
+    }
+
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        self.w1.approx_eq(&rhs.w1, delta) && self.w2.approx_eq(&rhs.w2, delta)
+    }
+
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        Self::new(self.w1.quantize(delta), self.w2.quantize(delta))
+    }
+
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        Self::new(self.w1.divide(&rhs.w1, divtype), self.w2.divide(&rhs.w2, divtype))
+    }
+
+    fn reverse(&self) -> Self {
+        Self::new(self.w1.reverse(), self.w2.reverse())
+    }
+
+    fn wtype() -> String {
+        format!("{}_LT_{}", W1::wtype(), W2::wtype())
+    }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match parse_pair(s, ',', |t| W1::parse_weight(t).ok(), |t| W2::parse_weight(t).ok()) {
+            Some((w1, w2)) => Ok(Self::new(w1, w2)),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
+}
+
+impl<W1: Weight + Path + fmt::Display, W2: Weight + Path + fmt::Display> fmt::Display for LexicographicWeight<W1, W2> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.w1, self.w2)
+    }
+}
+
+impl<W1: Weight + Path, W2: Weight + Path> FromStr for LexicographicWeight<W1, W2> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl<W1: Weight + Path + LeftSemiring, W2: Weight + Path + LeftSemiring> LeftSemiring for LexicographicWeight<W1, W2> {}
+impl<W1: Weight + Path + RightSemiring, W2: Weight + Path + RightSemiring> RightSemiring for LexicographicWeight<W1, W2> {}
+impl<W1: Weight + Path + Semiring, W2: Weight + Path + Semiring> Semiring for LexicographicWeight<W1, W2> {}
+impl<W1: Weight + Path + Commutative, W2: Weight + Path + Commutative> Commutative for LexicographicWeight<W1, W2> {}
+impl<W1: Weight + Path + Idempotent, W2: Weight + Path + Idempotent> Idempotent for LexicographicWeight<W1, W2> {}
+impl<W1: Weight + Path, W2: Weight + Path> Path for LexicographicWeight<W1, W2> {}
+
+impl<W1: Weight + Path + Idempotent, W2: Weight + Path + Idempotent> NaturalLess for LexicographicWeight<W1, W2> {
+    fn natural_less(&self, rhs: &Self) -> bool {
+        self.plus(rhs).eq(rhs) && !self.eq(rhs)
+    }
+}
@@ -15,10 +15,17 @@
 //! Shortest-Distance Problems", *Journal of Automata, Languages and
 //! Combinatorics* 7(3):321-350, 2002.
 
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 use std::option::Option;
+use std::error::Error;
+use std::hash::Hash;
+use std::cmp::Ordering;
 
-pub trait Weight: PartialEq + Clone + Debug {
+/// Weights are hashable so that they can be used as keys (e.g. in
+/// determinization subsets). For weights based on floats the hash is
+/// computed from the value after `quantize(None)`, so that `eq()`
+/// weights hash alike.
+pub trait Weight: PartialEq + Clone + Debug + Hash {
     fn is_member(&self) -> bool;
     fn plus(&self, rhs: &Self) -> Self;
     fn times(&self, rhs: &Self) -> Self;
@@ -30,8 +37,69 @@ pub trait Weight: PartialEq + Clone + Debug {
     fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self;
     fn reverse(&self) -> Self;
     fn wtype() -> String;
+    /// Reads a weight from its `Display` representation
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError>;
 }
 
+#[derive(Debug)]
+pub struct ParseWeightError {
+    pub message: String,
+}
+
+impl ParseWeightError {
+    pub fn new(wtype: &str, s: &str) -> Self {
+        ParseWeightError {message: format!("cannot parse '{}' as {} weight", s, wtype)}
+    }
+}
+
+impl fmt::Display for ParseWeightError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for ParseWeightError {}
+
+/// Parses the two components of a composite weight separated by
+/// `sep`. Since the components may themselves contain `sep`, each
+/// possible split is tried in turn.
+pub fn parse_pair<A, B, FA, FB>(s: &str, sep: char, fa: FA, fb: FB) -> Option<(A, B)>
+    where FA: Fn(&str) -> Option<A>,
+          FB: Fn(&str) -> Option<B>,
+{
+    for (i, _) in s.match_indices(sep) {
+        if let Some(a) = fa(&s[..i]) {
+            if let Some(b) = fb(&s[i + sep.len_utf8()..]) {
+                return Some((a, b))
+            }
+        }
+    }
+    None
+}
+
+/// Parses a list of `n` (or any number if `None`) components
+/// separated by `sep` as for `parse_pair()`.
+pub fn parse_list<A, FA>(s: &str, sep: char, n: Option<usize>, fa: &FA) -> Option<Vec<A>>
+    where FA: Fn(&str) -> Option<A>,
+{
+    match n {
+        Some(0) => None,
+        Some(1) => fa(s).map(|a| vec![a]),
+        _ => {
+            let rest = |t: &str| parse_list(t, sep, n.map(|n| n - 1), fa);
+            if let Some((a, mut v)) = parse_pair(s, sep, fa, rest) {
+                v.insert(0, a);
+                Some(v)
+            } else if n.is_none() {
+                fa(s).map(|a| vec![a])
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DivideType {
     Divleft,
     Divright,
@@ -64,6 +132,38 @@ pub trait NaturalLess: Weight + Idempotent + Path { //Path?
     fn natural_less(&self, rhs: &Self) -> bool;
 }
 
+/// Total order derived from `NaturalLess`, e.g. for sorting or
+/// priority queues
+pub fn natural_cmp<W: NaturalLess>(w1: &W, w2: &W) -> Ordering {
+    if w1.eq(w2) {
+        Ordering::Equal
+    } else if w1.natural_less(w2) {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+/// Wraps a weight to implement `Ord` via `natural_cmp()` so that it
+/// can be used in `BinaryHeap`, `BTreeMap`, etc. Only valid for
+/// members of the semiring (e.g. `none()` is not equal to itself).
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub struct NaturalOrd<W: NaturalLess>(pub W);
+
+impl<W: NaturalLess> Eq for NaturalOrd<W> {}
+
+impl<W: NaturalLess> PartialOrd for NaturalOrd<W> {
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl<W: NaturalLess> Ord for NaturalOrd<W> {
+    fn cmp(&self, rhs: &Self) -> Ordering {
+        natural_cmp(&self.0, &rhs.0)
+    }
+}
+
 
 /// Power is the iterated product for arbitrary semirings such that
 /// Power(w, 0) is One() for the semiring, and
@@ -79,4 +179,11 @@ pub fn power<T: Weight>(w: &T, n: u8) -> T {
 
 pub mod float;
 pub mod floatweight;
+pub mod booleanweight;
+pub mod productweight;
+pub mod stringweight;
+pub mod lexicographicweight;
+pub mod powerweight;
+pub mod expectationweight;
+pub mod latticeweight;
 pub mod test;
@@ -0,0 +1,319 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+// This file contains portions of code ported from OpenFst
+// (http://www.openfst.org) under the following licence and
+// attribution:
+//
+// """
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Copyright 2005-2010 Google, Inc.
+// Author: riley@google.com (Michael Riley)
+// """
+////////////////////////////////////////////////////////////////////////////////
+
+//! This module implements the Cartesian power of a Weight type, which
+//! can be used to accumulate a vector of feature scores along paths.
+//! `PowerWeight` has a fixed number of components while
+//! `SparsePowerWeight` maps arbitrary indices to weights and stores
+//! only those which differ from a default value. All operations are
+//! element-wise.
+
+extern crate serde;
+use self::serde::{Serialize, Deserialize};
+
+use super::*;
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use std::collections::BTreeMap;
+
+//POWER SEMIRING: (W^N, ⊕^N, ⊗^N, (0,..,0), (1,..,1))
+#[derive(Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "PowerWeightData<W>")]
+pub struct PowerWeight<W: Weight, const N: usize> {
+    vals: Vec<W>
+}
+
+// Deserialised form of `PowerWeight`, converted after checking the
+// number of components
+#[derive(Deserialize)]
+struct PowerWeightData<W> {
+    vals: Vec<W>
+}
+
+impl<W: Weight, const N: usize> TryFrom<PowerWeightData<W>> for PowerWeight<W, N> {
+    type Error = String;
+
+    fn try_from(data: PowerWeightData<W>) -> Result<Self, Self::Error> {
+        if data.vals.len() == N {
+            Ok(PowerWeight {vals: data.vals})
+        } else {
+            Err(format!("expected {} components, found {}", N, data.vals.len()))
+        }
+    }
+}
+
+impl<W: Weight, const N: usize> PowerWeight<W, N> {
+    pub fn new(vals: Vec<W>) -> Self {
+        assert!(vals.len() == N);
+        PowerWeight {vals}
+    }
+
+    /// A weight with all components set to `w`
+    pub fn from_weight(w: W) -> Self {
+        PowerWeight {vals: vec![w; N]}
+    }
+
+    pub fn value(&self, index: usize) -> &W {
+        &self.vals[index]
+    }
+
+    pub fn values(&self) -> &[W] {
+        &self.vals
+    }
+
+    fn map2<F: Fn(&W, &W) -> W>(&self, rhs: &Self, f: F) -> Self {
+        debug_assert_eq!(self.vals.len(), N);
+        debug_assert_eq!(rhs.vals.len(), N);
+        PowerWeight {vals: self.vals.iter().zip(&rhs.vals).map(|(w1, w2)| f(w1, w2)).collect()}
+    }
+}
+
+impl<W: Weight, const N: usize> Weight for PowerWeight<W, N> {
+    fn plus(&self, rhs: &Self) -> Self {
+        self.map2(rhs, |w1, w2| w1.plus(w2))
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        self.map2(rhs, |w1, w2| w1.times(w2))
+    }
+
+    fn zero() -> Self {
+        Self::from_weight(W::zero())
+    }
+
+    fn one() -> Self {
+        Self::from_weight(W::one())
+    }
+
+    fn none() -> Self {
+        Self::from_weight(W::none())
+    }
+
+    fn is_member(&self) -> bool {
+        self.vals.len() == N && self.vals.iter().all(|w| w.is_member())
+    }
+
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        self.vals.iter().zip(&rhs.vals).all(|(w1, w2)| w1.approx_eq(w2, delta))
+    }
+
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        PowerWeight {vals: self.vals.iter().map(|w| w.quantize(delta)).collect()}
+    }
+
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        self.map2(rhs, |w1, w2| w1.divide(w2, divtype))
+    }
+
+    fn reverse(&self) -> Self {
+        PowerWeight {vals: self.vals.iter().map(|w| w.reverse()).collect()}
+    }
+
+    fn wtype() -> String {
+        format!("{}_^{}", W::wtype(), N)
+    }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match parse_list(s, ',', Some(N), &|t| W::parse_weight(t).ok()) {
+            Some(vals) => Ok(Self::new(vals)),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
+}
+
+impl<W: Weight + fmt::Display, const N: usize> fmt::Display for PowerWeight<W, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let vals: Vec<String> = self.vals.iter().map(|w| w.to_string()).collect();
+        write!(f, "{}", vals.join(","))
+    }
+}
+
+impl<W: Weight, const N: usize> FromStr for PowerWeight<W, N> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl<W: Weight + LeftSemiring, const N: usize> LeftSemiring for PowerWeight<W, N> {}
+impl<W: Weight + RightSemiring, const N: usize> RightSemiring for PowerWeight<W, N> {}
+impl<W: Weight + Semiring, const N: usize> Semiring for PowerWeight<W, N> {}
+impl<W: Weight + Commutative, const N: usize> Commutative for PowerWeight<W, N> {}
+impl<W: Weight + Idempotent, const N: usize> Idempotent for PowerWeight<W, N> {}
+
+////////////////////////////////////////////////////////////////////////////////
+//SPARSE POWER SEMIRING: as above over unbounded indices, with all
+//components not explicitly stored equal to `default`
+#[derive(Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
+pub struct SparsePowerWeight<W: Weight> {
+    default: W,
+    vals: BTreeMap<usize, W>
+}
+
+impl<W: Weight> SparsePowerWeight<W> {
+    /// A weight with all components set to `default`
+    pub fn new(default: W) -> Self {
+        SparsePowerWeight {default, vals: BTreeMap::new()}
+    }
+
+    pub fn default_value(&self) -> &W {
+        &self.default
+    }
+
+    pub fn value(&self, index: usize) -> &W {
+        self.vals.get(&index).unwrap_or(&self.default)
+    }
+
+    pub fn set_value(&mut self, index: usize, w: W) {
+        if w == self.default {
+            self.vals.remove(&index);
+        } else {
+            self.vals.insert(index, w);
+        }
+    }
+
+    /// Iterates over the components which differ from the default
+    pub fn iter(&self) -> impl Iterator<Item=(usize, &W)> {
+        self.vals.iter().map(|(i, w)| (*i, w))
+    }
+
+    fn map<F: Fn(&W) -> W>(&self, f: F) -> Self {
+        let mut result = Self::new(f(&self.default));
+        for (i, w) in self.iter() {
+            result.set_value(i, f(w));
+        }
+        result
+    }
+
+    fn map2<F: Fn(&W, &W) -> W>(&self, rhs: &Self, f: F) -> Self {
+        let mut result = Self::new(f(&self.default, &rhs.default));
+        for i in self.vals.keys().chain(rhs.vals.keys()) {
+            result.set_value(*i, f(self.value(*i), rhs.value(*i)));
+        }
+        result
+    }
+}
+
+impl<W: Weight> Weight for SparsePowerWeight<W> {
+    fn plus(&self, rhs: &Self) -> Self {
+        self.map2(rhs, |w1, w2| w1.plus(w2))
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        self.map2(rhs, |w1, w2| w1.times(w2))
+    }
+
+    fn zero() -> Self {
+        Self::new(W::zero())
+    }
+
+    fn one() -> Self {
+        Self::new(W::one())
+    }
+
+    fn none() -> Self {
+        Self::new(W::none())
+    }
+
+    fn is_member(&self) -> bool {
+        self.default.is_member() && self.vals.values().all(|w| w.is_member())
+    }
+
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        self.default.approx_eq(&rhs.default, delta) &&
+            self.vals.keys().chain(rhs.vals.keys()).all(|i| self.value(*i).approx_eq(rhs.value(*i), delta))
+    }
+
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        self.map(|w| w.quantize(delta))
+    }
+
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        self.map2(rhs, |w1, w2| w1.divide(w2, divtype))
+    }
+
+    fn reverse(&self) -> Self {
+        self.map(|w| w.reverse())
+    }
+
+    fn wtype() -> String {
+        format!("{}_^n", W::wtype())
+    }
+
+    // Components are written as `index:weight` after the default
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        let component = |t: &str| {
+            let mut fields = t.splitn(2, ':');
+            let i = fields.next()?.parse().ok()?;
+            Some((i, W::parse_weight(fields.next()?).ok()?))
+        };
+        let components = |t: &str| {
+            if t.is_empty() {
+                Some(Vec::new())
+            } else {
+                parse_list(t, ',', None, &component)
+            }
+        };
+        match parse_pair(s, ';', |t| W::parse_weight(t).ok(), components) {
+            Some((default, components)) => {
+                let mut w = Self::new(default);
+                for (i, v) in components {
+                    w.set_value(i, v);
+                }
+                Ok(w)
+            },
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
+}
+
+impl<W: Weight + fmt::Display> fmt::Display for SparsePowerWeight<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let vals: Vec<String> = self.iter().map(|(i, w)| format!("{}:{}", i, w)).collect();
+        write!(f, "{};{}", self.default, vals.join(","))
+    }
+}
+
+impl<W: Weight> FromStr for SparsePowerWeight<W> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl<W: Weight + LeftSemiring> LeftSemiring for SparsePowerWeight<W> {}
+impl<W: Weight + RightSemiring> RightSemiring for SparsePowerWeight<W> {}
+impl<W: Weight + Semiring> Semiring for SparsePowerWeight<W> {}
+impl<W: Weight + Commutative> Commutative for SparsePowerWeight<W> {}
+impl<W: Weight + Idempotent> Idempotent for SparsePowerWeight<W> {}
@@ -0,0 +1,136 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+// This file contains portions of code ported from OpenFst
+// (http://www.openfst.org) under the following licence and
+// attribution:
+//
+// """
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Copyright 2005-2010 Google, Inc.
+// Author: riley@google.com (Michael Riley)
+// """
+////////////////////////////////////////////////////////////////////////////////
+
+//! This module implements the product of two Weight types, which can
+//! be used to carry two scores (e.g. acoustic and language model
+//! costs) on each arc. See the source file `test_semiring.rs` for
+//! simple examples of intended use.
+
+extern crate serde;
+use self::serde::{Serialize, Deserialize};
+
+use super::*;
+
+use std::fmt;
+use std::str::FromStr;
+
+//PRODUCT SEMIRING: (W1 x W2, ⊕1 x ⊕2, ⊗1 x ⊗2, (0,0), (1,1))
+#[derive(Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
+pub struct ProductWeight<W1: Weight, W2: Weight> {
+    w1: W1,
+    w2: W2
+}
+
+impl<W1: Weight, W2: Weight> ProductWeight<W1, W2> {
+    pub fn new(w1: W1, w2: W2) -> Self {
+        ProductWeight {w1, w2}
+    }
+
+    pub fn value1(&self) -> &W1 {
+        &self.w1
+    }
+
+    pub fn value2(&self) -> &W2 {
+        &self.w2
+    }
+}
+
+impl<W1: Weight, W2: Weight> Weight for ProductWeight<W1, W2> {
+    fn plus(&self, rhs: &Self) -> Self {
+        Self::new(self.w1.plus(&rhs.w1), self.w2.plus(&rhs.w2))
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        Self::new(self.w1.times(&rhs.w1), self.w2.times(&rhs.w2))
+    }
+
+    fn zero() -> Self {
+        Self::new(W1::zero(), W2::zero())
+    }
+
+    fn one() -> Self {
+        Self::new(W1::one(), W2::one())
+    }
+
+    fn none() -> Self {
+        Self::new(W1::none(), W2::none())
+    }
+
+    fn is_member(&self) -> bool {
+        self.w1.is_member() && self.w2.is_member()
+    }
+
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        self.w1.approx_eq(&rhs.w1, delta) && self.w2.approx_eq(&rhs.w2, delta)
+    }
+
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        Self::new(self.w1.quantize(delta), self.w2.quantize(delta))
+    }
+
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        Self::new(self.w1.divide(&rhs.w1, divtype), self.w2.divide(&rhs.w2, divtype))
+    }
+
+    fn reverse(&self) -> Self {
+        Self::new(self.w1.reverse(), self.w2.reverse())
+    }
+
+    fn wtype() -> String {
+        format!("{}_X_{}", W1::wtype(), W2::wtype())
+    }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match parse_pair(s, ',', |t| W1::parse_weight(t).ok(), |t| W2::parse_weight(t).ok()) {
+            Some((w1, w2)) => Ok(Self::new(w1, w2)),
+            None => Err(ParseWeightError::new(&Self::wtype(), s))
+        }
+    }
+}
+
+impl<W1: Weight + fmt::Display, W2: Weight + fmt::Display> fmt::Display for ProductWeight<W1, W2> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.w1, self.w2)
+    }
+}
+
+impl<W1: Weight, W2: Weight> FromStr for ProductWeight<W1, W2> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl<W1: Weight + LeftSemiring, W2: Weight + LeftSemiring> LeftSemiring for ProductWeight<W1, W2> {}
+impl<W1: Weight + RightSemiring, W2: Weight + RightSemiring> RightSemiring for ProductWeight<W1, W2> {}
+impl<W1: Weight + Semiring, W2: Weight + Semiring> Semiring for ProductWeight<W1, W2> {}
+impl<W1: Weight + Commutative, W2: Weight + Commutative> Commutative for ProductWeight<W1, W2> {}
+impl<W1: Weight + Idempotent, W2: Weight + Idempotent> Idempotent for ProductWeight<W1, W2> {}
@@ -0,0 +1,269 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+// This file contains portions of code ported from OpenFst
+// (http://www.openfst.org) under the following licence and
+// attribution:
+//
+// """
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Copyright 2005-2010 Google, Inc.
+// Author: riley@google.com (Michael Riley)
+// """
+////////////////////////////////////////////////////////////////////////////////
+
+//! This module implements Weight types which are strings of labels,
+//! as required for transducer determinization and label pushing, and
+//! the Gallic weight which pairs such a string with another Weight.
+
+extern crate serde;
+use self::serde::{Serialize, Deserialize};
+
+use super::*;
+use super::productweight::ProductWeight;
+use super::super::Label;
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::marker::PhantomData;
+
+/// Determines whether a `StringWeight` sums to the longest common
+/// prefix (`StringLeft`) or suffix (`StringRight`).
+pub trait StringType: Clone + Debug + PartialEq + Hash {
+    fn is_left() -> bool;
+}
+
+#[derive(Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
+pub struct StringLeft;
+
+#[derive(Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
+pub struct StringRight;
+
+impl StringType for StringLeft {
+    fn is_left() -> bool { true }
+}
+
+impl StringType for StringRight {
+    fn is_left() -> bool { false }
+}
+
+#[derive(Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
+enum StringValue {
+    Labels(Vec<Label>),
+    Infinity,
+    NoWeight,
+}
+
+//LEFT STRING SEMIRING: (longest common prefix, concatenation, Infinity, Epsilon)
+//RIGHT STRING SEMIRING: (longest common suffix, concatenation, Infinity, Epsilon)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StringWeight<S: StringType> {
+    val: StringValue,
+    stype: PhantomData<S>
+}
+
+impl<S: StringType> StringWeight<S> {
+    fn from_value(val: StringValue) -> Self {
+        StringWeight { val, stype: PhantomData }
+    }
+
+    pub fn new(labels: Vec<Label>) -> Self {
+        Self::from_value(StringValue::Labels(labels))
+    }
+
+    /// The string of a single label (`Weight::one()` if epsilon)
+    pub fn from_label(label: Label) -> Self {
+        if label == 0 {
+            Self::new(Vec::new())
+        } else {
+            Self::new(vec![label])
+        }
+    }
+
+    /// The labels of the string, `None` for `zero()` and `none()`
+    pub fn labels(&self) -> Option<&[Label]> {
+        match self.val {
+            StringValue::Labels(ref v) => Some(v),
+            _ => None
+        }
+    }
+}
+
+impl<S: StringType> Weight for StringWeight<S> {
+    fn plus(&self, rhs: &Self) -> Self {
+        match (&self.val, &rhs.val) {
+            (StringValue::NoWeight, _) | (_, StringValue::NoWeight) => Self::none(),
+            (StringValue::Infinity, _) => rhs.clone(),
+            (_, StringValue::Infinity) => self.clone(),
+            (StringValue::Labels(v1), StringValue::Labels(v2)) => {
+                if S::is_left() {
+                    let n = v1.iter().zip(v2).take_while(|&(a, b)| a == b).count();
+                    Self::new(v1[..n].to_vec())
+                } else {
+                    let n = v1.iter().rev().zip(v2.iter().rev()).take_while(|&(a, b)| a == b).count();
+                    Self::new(v1[v1.len() - n..].to_vec())
+                }
+            }
+        }
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        match (&self.val, &rhs.val) {
+            (StringValue::NoWeight, _) | (_, StringValue::NoWeight) => Self::none(),
+            (StringValue::Infinity, _) | (_, StringValue::Infinity) => Self::zero(),
+            (StringValue::Labels(v1), StringValue::Labels(v2)) => {
+                let mut v = v1.clone();
+                v.extend_from_slice(v2);
+                Self::new(v)
+            }
+        }
+    }
+
+    fn zero() -> Self {
+        Self::from_value(StringValue::Infinity)
+    }
+
+    fn one() -> Self {
+        Self::new(Vec::new())
+    }
+
+    fn none() -> Self {
+        Self::from_value(StringValue::NoWeight)
+    }
+
+    fn is_member(&self) -> bool {
+        self.val != StringValue::NoWeight
+    }
+
+    #[allow(unused_variables)]
+    fn approx_eq(&self, rhs: &Self, delta: Option<f32>) -> bool {
+        self.eq(rhs)
+    }
+
+    #[allow(unused_variables)]
+    fn quantize(&self, delta: Option<f32>) -> Self {
+        self.clone()
+    }
+
+    // Left strings can only be divided on the left and right strings
+    // on the right (`None` selects the appropriate side).
+    fn divide(&self, rhs: &Self, divtype: Option<DivideType>) -> Self {
+        let valid = match divtype {
+            None => true,
+            Some(DivideType::Divleft) => S::is_left(),
+            Some(DivideType::Divright) => !S::is_left(),
+            Some(DivideType::Divany) => false,
+        };
+        if !valid {
+            return Self::none()
+        }
+        match (&self.val, &rhs.val) {
+            (StringValue::NoWeight, _) | (_, StringValue::NoWeight) => Self::none(),
+            (_, StringValue::Infinity) => Self::none(),
+            (StringValue::Infinity, _) => Self::zero(),
+            (StringValue::Labels(v1), StringValue::Labels(v2)) => {
+                if S::is_left() && v1.starts_with(v2) {
+                    Self::new(v1[v2.len()..].to_vec())
+                } else if !S::is_left() && v1.ends_with(v2) {
+                    Self::new(v1[..v1.len() - v2.len()].to_vec())
+                } else {
+                    Self::none()
+                }
+            }
+        }
+    }
+
+    // Reverses the labels. As `reverse()` returns the same type, the
+    // result is still a left (or right) string, whereas in OpenFst the
+    // reverse of a left string is a right string: `plus()` of reversed
+    // weights is therefore not the reverse of their `plus()`.
+    fn reverse(&self) -> Self {
+        match self.val {
+            StringValue::Labels(ref v) => Self::new(v.iter().rev().cloned().collect()),
+            _ => self.clone(),
+        }
+    }
+
+    fn wtype() -> String {
+        if S::is_left() {
+            String::from("left_string")
+        } else {
+            String::from("right_string")
+        }
+    }
+
+    fn parse_weight(s: &str) -> Result<Self, ParseWeightError> {
+        match s {
+            "Epsilon" => Ok(Self::one()),
+            "Infinity" => Ok(Self::zero()),
+            "None" => Ok(Self::none()),
+            _ => {
+                let labels: Result<Vec<Label>, _> = s.split('_').map(|l| l.parse()).collect();
+                labels.map(Self::new).map_err(|_| ParseWeightError::new(&Self::wtype(), s))
+            }
+        }
+    }
+}
+
+impl<S: StringType> fmt::Display for StringWeight<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.val {
+            StringValue::Labels(ref v) if v.is_empty() => write!(f, "Epsilon"),
+            StringValue::Labels(ref v) => {
+                let labels: Vec<String> = v.iter().map(|l| l.to_string()).collect();
+                write!(f, "{}", labels.join("_"))
+            },
+            StringValue::Infinity => write!(f, "Infinity"),
+            StringValue::NoWeight => write!(f, "None"),
+        }
+    }
+}
+
+impl<S: StringType> PartialEq for StringWeight<S> {
+    fn eq(&self, rhs: &Self) -> bool {
+        match (&self.val, &rhs.val) {
+            (StringValue::Labels(v1), StringValue::Labels(v2)) => v1 == v2,
+            (StringValue::Infinity, StringValue::Infinity) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<S: StringType> Hash for StringWeight<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.val.hash(state);
+    }
+}
+
+impl<S: StringType> FromStr for StringWeight<S> {
+    type Err = ParseWeightError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_weight(s)
+    }
+}
+
+impl LeftSemiring for StringWeight<StringLeft> {}
+impl RightSemiring for StringWeight<StringRight> {}
+impl<S: StringType> Idempotent for StringWeight<S> {}
+
+////////////////////////////////////////////////////////////////////////////////
+//GALLIC SEMIRING: product of a left string (output labels) and a weight
+pub type GallicWeight<W> = ProductWeight<StringWeight<StringLeft>, W>;
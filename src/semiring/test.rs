@@ -5,23 +5,86 @@
 //
 // See the "LICENCE" file for information on usage and redistribution
 // of this file.
+
+// This file contains portions of code ported from OpenFst
+// (http://www.openfst.org) under the following licence and
+// attribution:
+//
+// """
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Copyright 2005-2010 Google, Inc.
+// Author: riley@google.com (Michael Riley)
+// """
 ////////////////////////////////////////////////////////////////////////////////
 
-//! This module implements the generation of random weights for
-//! testing purposes. See the source file `test_semiring.rs` for
-//! simple examples of intended use.
+//! This module implements the generation of random weights and checks
+//! that a Weight type satisfies the semiring axioms, for testing
+//! purposes. A new Weight type implementing `RandomWeight` can be
+//! validated with a single call, e.g.:
+//!
+//! ```no_run
+//! extern crate rand;
+//! extern crate wfst;
+//! use rand::SeedableRng;
+//! use rand::rngs::StdRng;
+//! use wfst::semiring::floatweight::TropicalWeight;
+//! use wfst::semiring::test::check_path_semiring;
+//!
+//! let mut rng: StdRng = SeedableRng::seed_from_u64(777);
+//! check_path_semiring::<TropicalWeight<f32>>(&mut rng, 10000, true);
+//! ```
+//!
+//! See the source file `test_semiring.rs` for further examples.
 
 extern crate rand;
 use self::rand::{Rng};
 use self::rand::rngs::{StdRng};
+extern crate serde;
+use self::serde::Serialize;
+use self::serde::de::DeserializeOwned;
+extern crate bincode;
+use self::bincode::{serialize, deserialize};
+
+use std::fmt::Display;
 
 use super::*;
+use super::super::Label;
 use super::float::Float;
-use super::floatweight::{TropicalWeight, LogWeight, MinmaxWeight};
+use super::floatweight::{TropicalWeight, LogWeight, MinmaxWeight, SignedLogWeight, RealWeight, ArcticWeight};
+use super::booleanweight::BooleanWeight;
+use super::productweight::ProductWeight;
+use super::lexicographicweight::LexicographicWeight;
+use super::powerweight::{PowerWeight, SparsePowerWeight};
+use super::expectationweight::{ExpectationWeight, Module};
+use super::latticeweight::LatticeWeight;
+use super::stringweight::{StringWeight, StringType};
 
 //////////////////////////DEFINE HOW DIFFERENT WEIGHTS CAN BE CREATED FROM U32
 pub trait RandomWeight: Weight {
     fn from_u32(u32) -> Self;
+
+    /// Generates a random weight, by default from an integer in
+    /// [0, K) or `Self::zero()`. Composite weights override this to
+    /// generate each component independently.
+    fn random(rng: &mut StdRng, allow_zero: bool) -> Self {
+        let n = rng.gen_range(0, K + allow_zero as u32);
+        if allow_zero && n == K {
+            Self::zero()
+        } else {
+            Self::from_u32(n)
+        }
+    }
 }
 
 impl<T: Float<T>> RandomWeight for TropicalWeight<T> {
@@ -42,6 +105,136 @@ impl<T: Float<T>> RandomWeight for MinmaxWeight<T> {
     }
 }
 
+impl<T: Float<T>> RandomWeight for RealWeight<T> {
+    fn from_u32(n: u32) -> Self {
+        Self::new(Some(T::from_u32(n)))
+    }
+}
+
+impl<T: Float<T>> RandomWeight for ArcticWeight<T> {
+    fn from_u32(n: u32) -> Self {
+        Self::new(Some(T::from_u32(n)))
+    }
+}
+
+impl RandomWeight for BooleanWeight {
+    fn from_u32(n: u32) -> Self {
+        Self::new(n % 2 == 1)
+    }
+}
+
+impl<T: Float<T>> RandomWeight for SignedLogWeight<T> {
+    fn from_u32(n: u32) -> Self {
+        Self::new(false, Some(T::from_u32(n)))
+    }
+
+    fn random(rng: &mut StdRng, allow_zero: bool) -> Self {
+        let n = rng.gen_range(0, K + allow_zero as u32);
+        if allow_zero && n == K {
+            Self::zero()
+        } else {
+            Self::new(rng.gen(), Some(T::from_u32(n)))
+        }
+    }
+}
+
+impl<T: Float<T>> RandomWeight for LatticeWeight<T> {
+    fn from_u32(n: u32) -> Self {
+        Self::from_costs(T::from_u32(n), T::from_u32(n))
+    }
+
+    fn random(rng: &mut StdRng, allow_zero: bool) -> Self {
+        let n = rng.gen_range(0, K + allow_zero as u32);
+        if allow_zero && n == K {
+            Self::zero()
+        } else {
+            Self::from_costs(T::from_u32(n), T::from_u32(rng.gen_range(0, K)))
+        }
+    }
+}
+
+impl<S: StringType> RandomWeight for StringWeight<S> {
+    fn from_u32(n: u32) -> Self {
+        Self::new(vec![n as Label + 1])
+    }
+
+    //Strings of up to K labels in [1, K]
+    fn random(rng: &mut StdRng, allow_zero: bool) -> Self {
+        let n = rng.gen_range(0, K + 1 + allow_zero as u32);
+        if allow_zero && n == K + 1 {
+            Self::zero()
+        } else {
+            Self::new((0..n).map(|_| rng.gen_range(1, K + 1) as Label).collect())
+        }
+    }
+}
+
+impl<W1: RandomWeight, W2: RandomWeight> RandomWeight for ProductWeight<W1, W2> {
+    fn from_u32(n: u32) -> Self {
+        Self::new(W1::from_u32(n), W2::from_u32(n))
+    }
+
+    fn random(rng: &mut StdRng, allow_zero: bool) -> Self {
+        Self::new(W1::random(rng, allow_zero), W2::random(rng, allow_zero))
+    }
+}
+
+impl<W: RandomWeight, const N: usize> RandomWeight for PowerWeight<W, N> {
+    fn from_u32(n: u32) -> Self {
+        Self::from_weight(W::from_u32(n))
+    }
+
+    fn random(rng: &mut StdRng, allow_zero: bool) -> Self {
+        Self::new((0..N).map(|_| W::random(rng, allow_zero)).collect())
+    }
+}
+
+impl<W: RandomWeight> RandomWeight for SparsePowerWeight<W> {
+    fn from_u32(n: u32) -> Self {
+        Self::new(W::from_u32(n))
+    }
+
+    //Sets a random default and up to K components with indices in [0, 2K)
+    fn random(rng: &mut StdRng, allow_zero: bool) -> Self {
+        let mut w = Self::new(W::random(rng, allow_zero));
+        for _ in 0..rng.gen_range(0, K + 1) {
+            let i = rng.gen_range(0, 2 * K) as usize;
+            w.set_value(i, W::random(rng, allow_zero));
+        }
+        w
+    }
+}
+
+impl<W1: RandomWeight + Path, W2: RandomWeight + Path> RandomWeight for LexicographicWeight<W1, W2> {
+    fn from_u32(n: u32) -> Self {
+        Self::new(W1::from_u32(n), W2::from_u32(n))
+    }
+
+    //Components must be either both zero or both non-zero
+    fn random(rng: &mut StdRng, allow_zero: bool) -> Self {
+        if allow_zero && rng.gen_range(0, K + 1) == K {
+            Self::zero()
+        } else {
+            Self::new(W1::random(rng, false), W2::random(rng, false))
+        }
+    }
+}
+
+impl<W: RandomWeight, V: RandomWeight + Module<W>> RandomWeight for ExpectationWeight<W, V> {
+    fn from_u32(n: u32) -> Self {
+        Self::new(W::from_u32(n), V::from_u32(n))
+    }
+
+    //A zero probability must have a zero value for `zero()` to annihilate
+    fn random(rng: &mut StdRng, allow_zero: bool) -> Self {
+        if allow_zero && rng.gen_range(0, K + 1) == K {
+            Self::zero()
+        } else {
+            Self::new(W::random(rng, false), V::random(rng, true))
+        }
+    }
+}
+
 
 //////////////////////////////DEFINE HOW RANDOM WEIGHTS CAN BE CREATED USING RNG
 
@@ -54,11 +247,313 @@ pub trait RandomWeightGenerator {
 
 impl RandomWeightGenerator for StdRng {
     fn genweight<T: RandomWeight>(&mut self, allow_zero: bool) -> T {
-        let n = self.gen_range(0, K + allow_zero as u32);
-        if allow_zero && n == K {
-            T::zero()
-        } else {
-            T::from_u32(n)
+        T::random(self, allow_zero)
+    }
+}
+
+
+//////////////////////////////////////////////////////CHECK THE SEMIRING AXIOMS
+
+// Checks (plus, times, zero, one) defines a semiring, except for
+// distributivity which is checked separately for each side.
+fn check_axioms<T: Weight>(w1: &T, w2: &T, w3: &T) {
+    // Checks that the operations are closed.
+    assert!(w1.plus(w2).is_member());
+    assert!(w1.times(w2).is_member());
+
+    // Checks that the operations are associative.
+    assert!(w1.plus(&w2.plus(w3)).approx_eq(&w1.plus(w2).plus(w3), None));
+    assert!(w1.times(&w2.times(w3)).approx_eq(&w1.times(w2).times(w3), None));
+
+    // Checks the identity elements.
+    assert!(w1.plus(&T::zero()).eq(w1));
+    assert!(T::zero().plus(w1).eq(w1));
+    assert!(w1.times(&T::one()).eq(w1));
+    assert!(T::one().times(w1).eq(w1));
+
+    // Check the no weight element.
+    assert!(!T::none().is_member());
+    assert!(!w1.plus(&T::none()).is_member());
+    assert!(!T::none().plus(w1).is_member());
+    assert!(!w1.times(&T::none()).is_member());
+    assert!(!T::none().times(w1).is_member());
+
+    // Checks that plus commutes (required of any semiring).
+    assert!(w1.plus(w2).approx_eq(&w2.plus(w1), None));
+
+    // Checks zero() is the annihilator.
+    assert!(w1.times(&T::zero()).eq(&T::zero()));
+    assert!(T::zero().times(w1).eq(&T::zero()));
+    
+    // Check power(w, 0) is one()
+    assert!(power(w1, 0).eq(&T::one()));
+
+    // Check power(w, 1) is w
+    assert!(power(w1, 1).eq(w1));
+
+    // Check power(w, 3) is times(w, times(w, w))
+    assert!(power(w1, 3).eq(&w1.times(&w1.times(w1))));
+}
+
+fn check_left_distributivity<T: Weight + LeftSemiring>(w1: &T, w2: &T, w3: &T) {
+    assert!(w1.times(&w2.plus(w3)).approx_eq(&w1.times(w2).plus(&w1.times(w3)), None));
+}
+
+fn check_right_distributivity<T: Weight + RightSemiring>(w1: &T, w2: &T, w3: &T) {
+    assert!(w1.plus(w2).times(w3).approx_eq(&w1.times(w3).plus(&w2.times(w3)), None));
+}
+
+// Checks times commutes.
+fn check_commutative<T: Weight + Commutative>(w1: &T, w2: &T) {
+    assert!(w1.times(w2).approx_eq(&w2.times(w1), None));
+}
+
+fn check_idempotent_plus<T: Weight + Idempotent>(w1: &T) {
+    assert!(w1.plus(w1).eq(w1));
+}
+
+fn check_idempotent_path<T: Weight + Idempotent + Path>(w1: &T, w2: &T) {
+    check_idempotent_plus(w1);
+    assert!(w1.plus(w2).eq(w1) || w1.plus(w2).eq(w2)); //Path
+}
+
+// Checks division operations
+fn check_left_division<T: Weight + LeftSemiring>(w1: &T, w2: &T) {
+    let p = w1.times(w2);
+    let d = p.divide(w1, Some(DivideType::Divleft));
+    if d.is_member() {
+        assert!(p.approx_eq(&w1.times(&d), None));
+    }
+    assert!(!w1.divide(&T::none(), Some(DivideType::Divleft)).is_member());
+    assert!(!T::none().divide(w1, Some(DivideType::Divleft)).is_member());
+}
+
+fn check_right_division<T: Weight + RightSemiring>(w1: &T, w2: &T) {
+    let p = w1.times(w2);
+    let d = p.divide(w2, Some(DivideType::Divright));
+    if d.is_member() {
+        assert!(p.approx_eq(&d.times(w2), None));
+    }
+    assert!(!w1.divide(&T::none(), Some(DivideType::Divright)).is_member());
+    assert!(!T::none().divide(w1, Some(DivideType::Divright)).is_member());
+}
+
+fn check_commutative_division<T: Weight + Semiring + Commutative>(w1: &T, w2: &T) {
+    let p = w1.times(w2);
+    let d = p.divide(w1, Some(DivideType::Divright));
+    if d.is_member() {
+        assert!(p.approx_eq(&d.times(w1), None));
+    }
+}
+
+// Reversing a weight of a left or right semiring gives a weight of
+// the opposite semiring, which is not a distinct type here (see
+// `StringWeight::reverse()`), so only `times()` is checked.
+fn check_reverse_times<T: Weight>(w1: &T, w2: &T) {
+    let rw1 = w1.reverse();
+    let rw2 = w2.reverse();
+
+    assert!(rw1.reverse().eq(w1));
+    assert!(w1.times(w2).reverse().eq(&rw2.times(&rw1)));
+}
+
+fn check_reverse<T: Weight>(w1: &T, w2: &T) {
+    check_reverse_times(w1, w2);
+    assert!(w1.plus(w2).reverse().eq(&w1.reverse().plus(&w2.reverse())));
+}
+
+// Checks eq() is an equivalence relation.
+fn check_equality<T: Weight>(w1: &T, w2: &T, w3: &T) {
+    // Checks reflexivity.
+    assert!(w1.eq(w1));
+
+    // Checks symmetry.
+    assert!(w1.eq(w2) == w2.eq(w1));
+
+    // Checks transitivity.
+    if w1.eq(w2) && w2.eq(w3) {
+        assert!(w1.eq(w3));
+    }
+}
+
+fn check_io<T: Weight + Display + Serialize + DeserializeOwned>(w1: &T) {
+    let encoded = serialize(w1).unwrap();
+    let ww: T = deserialize(&encoded).unwrap();
+    assert!(w1.eq(&ww));
+
+    let ww = T::parse_weight(&w1.to_string()).unwrap();
+    assert!(w1.eq(&ww));
+}
+
+fn check_clone<T: Weight>(w1: &T) {
+    let ww: T = w1.clone();
+    assert!(w1.eq(&ww));
+}
+
+// Checks properties that must hold for any weight
+fn check_common<T>(w1: &T, w2: &T, w3: &T)
+    where T: Weight + Display + Serialize + DeserializeOwned
+{
+    check_equality(w1, w2, w3);
+    check_io(w1);
+    check_clone(w1);
+}
+
+// Checks identities and properties that must hold for any semiring
+fn check_weights<T>(w1: &T, w2: &T, w3: &T, test_div: bool)
+    where T: Weight + Semiring + Display + Serialize + DeserializeOwned
+{
+    check_axioms(w1, w2, w3);
+    check_left_distributivity(w1, w2, w3);
+    check_right_distributivity(w1, w2, w3);
+    if test_div {
+        check_left_division(w1, w2);
+        check_right_division(w1, w2);
+    }
+    check_reverse(w1, w2);
+    check_common(w1, w2, w3);
+}
+
+/// Tests a variety of identities and properties that must hold for the
+/// Weight implementation to be a well-defined semiring, on
+/// `n_iterations` random triples of weights, panicking if any
+/// fails. Division is checked if `test_div`. Note that `approx_eq()`
+/// rather than `eq()` is used where the weights might be inexact.
+pub fn check_semiring<T>(rng: &mut StdRng, n_iterations: u32, test_div: bool)
+    where T: RandomWeight + Semiring + Display + Serialize + DeserializeOwned
+{
+    for _ in 0..n_iterations {
+        let w1 = rng.genweight::<T>(true);
+        let w2 = rng.genweight::<T>(true);
+        let w3 = rng.genweight::<T>(true);
+        check_weights(&w1, &w2, &w3, test_div);
+    }
+}
+
+/// As `check_semiring()`, additionally checking that `times()` commutes.
+pub fn check_commutative_semiring<T>(rng: &mut StdRng, n_iterations: u32, test_div: bool)
+    where T: RandomWeight + Semiring + Commutative + Display + Serialize + DeserializeOwned
+{
+    for _ in 0..n_iterations {
+        let w1 = rng.genweight::<T>(true);
+        let w2 = rng.genweight::<T>(true);
+        let w3 = rng.genweight::<T>(true);
+        check_weights(&w1, &w2, &w3, test_div);
+        check_commutative(&w1, &w2);
+        if test_div {
+            check_commutative_division(&w1, &w2);
+        }
+    }
+}
+
+/// As `check_commutative_semiring()`, additionally checking the
+/// idempotence and path properties.
+pub fn check_path_semiring<T>(rng: &mut StdRng, n_iterations: u32, test_div: bool)
+    where T: RandomWeight + Semiring + Commutative + Idempotent + Path + Display + Serialize + DeserializeOwned
+{
+    for _ in 0..n_iterations {
+        let w1 = rng.genweight::<T>(true);
+        let w2 = rng.genweight::<T>(true);
+        let w3 = rng.genweight::<T>(true);
+        check_weights(&w1, &w2, &w3, test_div);
+        check_commutative(&w1, &w2);
+        if test_div {
+            check_commutative_division(&w1, &w2);
+        }
+        check_idempotent_path(&w1, &w2);
+    }
+}
+
+/// As `check_semiring()` for a left semiring: only left
+/// distributivity and left division are checked.
+pub fn check_left_semiring<T>(rng: &mut StdRng, n_iterations: u32, test_div: bool)
+    where T: RandomWeight + LeftSemiring + Display + Serialize + DeserializeOwned
+{
+    for _ in 0..n_iterations {
+        let w1 = rng.genweight::<T>(true);
+        let w2 = rng.genweight::<T>(true);
+        let w3 = rng.genweight::<T>(true);
+        check_axioms(&w1, &w2, &w3);
+        check_left_distributivity(&w1, &w2, &w3);
+        if test_div {
+            check_left_division(&w1, &w2);
         }
+        check_reverse_times(&w1, &w2);
+        check_common(&w1, &w2, &w3);
+    }
+}
+
+/// As `check_semiring()` for a right semiring: only right
+/// distributivity and right division are checked.
+pub fn check_right_semiring<T>(rng: &mut StdRng, n_iterations: u32, test_div: bool)
+    where T: RandomWeight + RightSemiring + Display + Serialize + DeserializeOwned
+{
+    for _ in 0..n_iterations {
+        let w1 = rng.genweight::<T>(true);
+        let w2 = rng.genweight::<T>(true);
+        let w3 = rng.genweight::<T>(true);
+        check_axioms(&w1, &w2, &w3);
+        check_right_distributivity(&w1, &w2, &w3);
+        if test_div {
+            check_right_division(&w1, &w2);
+        }
+        check_reverse_times(&w1, &w2);
+        check_common(&w1, &w2, &w3);
+    }
+}
+
+/// Checks that `plus()` is idempotent, for weights that are
+/// idempotent but not path weights (which `check_path_semiring()`
+/// covers), in addition to one of the checks above.
+pub fn check_idempotent<T>(rng: &mut StdRng, n_iterations: u32)
+    where T: RandomWeight + Idempotent
+{
+    for _ in 0..n_iterations {
+        let w1 = rng.genweight::<T>(true);
+        check_idempotent_plus(&w1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::rand::SeedableRng;
+
+    #[test]
+    fn left_semiring() {
+        let mut rng: StdRng = SeedableRng::seed_from_u64(777);
+        check_left_semiring::<TropicalWeight<f32>>(&mut rng, 1000, true);
+        check_left_semiring::<LogWeight<f64>>(&mut rng, 1000, true);
+    }
+
+    #[test]
+    fn right_semiring() {
+        let mut rng: StdRng = SeedableRng::seed_from_u64(777);
+        check_right_semiring::<TropicalWeight<f32>>(&mut rng, 1000, true);
+        check_right_semiring::<LogWeight<f64>>(&mut rng, 1000, true);
+    }
+
+    #[test]
+    fn idempotent() {
+        let mut rng: StdRng = SeedableRng::seed_from_u64(777);
+        check_idempotent::<ProductWeight<TropicalWeight<f32>, MinmaxWeight<f32>>>(&mut rng, 1000);
+        check_idempotent::<PowerWeight<BooleanWeight, 2>>(&mut rng, 1000);
+    }
+
+    #[test]
+    fn strings() {
+        use super::super::stringweight::{StringLeft, StringRight, GallicWeight};
+        let mut rng: StdRng = SeedableRng::seed_from_u64(777);
+        check_left_semiring::<StringWeight<StringLeft>>(&mut rng, 1000, true);
+        check_right_semiring::<StringWeight<StringRight>>(&mut rng, 1000, true);
+        check_left_semiring::<GallicWeight<TropicalWeight<f32>>>(&mut rng, 1000, true);
+        check_idempotent::<StringWeight<StringLeft>>(&mut rng, 1000);
+
+        // As for the float weights, `none()` is not equal to any weight
+        assert!(StringWeight::<StringLeft>::none() != StringWeight::none());
+        assert!(StringWeight::<StringLeft>::none() != StringWeight::zero());
+        assert!(StringWeight::<StringLeft>::zero() == StringWeight::zero());
+        assert!(StringWeight::<StringLeft>::new(vec![1, 2]) == StringWeight::new(vec![1, 2]));
+        assert!(GallicWeight::<TropicalWeight<f32>>::none() != GallicWeight::none());
     }
 }
@@ -0,0 +1,205 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements a `SymbolTable` which maps between string
+//! symbols and integer labels. Fsts share tables through reference
+//! counted pointers (`Rc`), so that many Fsts can refer to the same
+//! table without copying it.
+//!
+//! Tables are read and written in the two-column text format used by
+//! OpenFst, i.e. one `symbol<TAB>label` entry per line.
+
+extern crate serde;
+use self::serde::{Serialize, Deserialize};
+
+use std::collections::{BTreeMap, HashMap};
+use std::collections::btree_map;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::iter::FromIterator;
+
+use super::Label;
+use super::Error;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(from = "SymbolTableRepr", into = "SymbolTableRepr")]
+pub struct SymbolTable {
+    name: String,
+    symbols: BTreeMap<Label, String>,
+    labels: HashMap<String, Label>,
+}
+
+// Only one direction of the mapping is serialised
+#[derive(Clone, Serialize, Deserialize)]
+struct SymbolTableRepr {
+    name: String,
+    symbols: Vec<(Label, String)>,
+}
+
+impl From<SymbolTableRepr> for SymbolTable {
+    fn from(repr: SymbolTableRepr) -> Self {
+        let mut symtab = SymbolTable::new(&repr.name);
+        for (label, sym) in repr.symbols {
+            symtab.add_symbol_with_label(&sym, label);
+        }
+        symtab
+    }
+}
+
+impl From<SymbolTable> for SymbolTableRepr {
+    fn from(symtab: SymbolTable) -> Self {
+        SymbolTableRepr { name: symtab.name,
+                          symbols: symtab.symbols.into_iter().collect() }
+    }
+}
+
+impl SymbolTable {
+    pub fn new(name: &str) -> Self {
+        SymbolTable { name: String::from(name),
+                      symbols: BTreeMap::new(),
+                      labels: HashMap::new() }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = String::from(name);
+    }
+
+    /// Adds a symbol with the next available label, returning its
+    /// label (the existing label if the symbol is already present).
+    pub fn add_symbol(&mut self, sym: &str) -> Label {
+        let label = self.available_label();
+        self.add_symbol_with_label(sym, label)
+    }
+
+    /// Adds a symbol with the given label, returning its label (the
+    /// existing label if the symbol is already present). Any symbol
+    /// previously assigned to `label` is replaced.
+    pub fn add_symbol_with_label(&mut self, sym: &str, label: Label) -> Label {
+        if let Some(&l) = self.labels.get(sym) {
+            return l
+        }
+        if let Some(old) = self.symbols.insert(label, String::from(sym)) {
+            self.labels.remove(&old);
+        }
+        self.labels.insert(String::from(sym), label);
+        label
+    }
+
+    pub fn find_label(&self, sym: &str) -> Option<Label> {
+        self.labels.get(sym).cloned()
+    }
+
+    pub fn find_symbol(&self, label: Label) -> Option<&str> {
+        self.symbols.get(&label).map(|s| s.as_str())
+    }
+
+    pub fn contains_label(&self, label: Label) -> bool {
+        self.symbols.contains_key(&label)
+    }
+
+    pub fn contains_symbol(&self, sym: &str) -> bool {
+        self.labels.contains_key(sym)
+    }
+
+    pub fn num_symbols(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// The label that `add_symbol()` will assign next (one more than
+    /// the highest label in the table).
+    pub fn available_label(&self) -> Label {
+        self.symbols.keys().next_back().map_or(0, |l| l + 1)
+    }
+
+    /// Iterates over `(label, symbol)` pairs in label order
+    pub fn iter(&self) -> btree_map::Iter<'_, Label, String> {
+        self.symbols.iter()
+    }
+
+    /// A checksum over the label/symbol pairs (not the name) which
+    /// can be used to check that two tables are compatible. Uses the
+    /// 64-bit FNV-1a hash so that it is stable across builds.
+    pub fn checksum(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+        let mut h = FNV_OFFSET;
+        for (label, sym) in &self.symbols {
+            for b in sym.bytes().chain(Some(b'\t')).chain(label.to_string().bytes()).chain(Some(b'\n')) {
+                h ^= u64::from(b);
+                h = h.wrapping_mul(FNV_PRIME);
+            }
+        }
+        h
+    }
+
+    /// Reads a table in text format (`symbol<TAB>label` per line)
+    pub fn read_text<R: BufRead>(name: &str, reader: R) -> Result<Self, Error> {
+        let mut symtab = SymbolTable::new(name);
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.is_empty() {
+                continue
+            }
+            if fields.len() != 2 {
+                return Err(Error::format_at(i + 1, "wrong number of fields"))
+            }
+            let label = fields[1].parse::<Label>().map_err(|_| Error::format_at(i + 1, &format!("bad label '{}'", fields[1])))?;
+            symtab.add_symbol_with_label(fields[0], label);
+        }
+        Ok(symtab)
+    }
+
+    /// Writes the table in text format (`symbol<TAB>label` per line)
+    pub fn write_text<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        for (label, sym) in &self.symbols {
+            writeln!(writer, "{}\t{}", sym, label)?;
+        }
+        Ok(())
+    }
+
+    /// Loads a table from a text file, named after the file
+    pub fn load(filename: &str) -> Result<Self, Error> {
+        let fh = File::open(filename)?;
+        Self::read_text(filename, BufReader::new(fh))
+    }
+
+    /// Saves the table to a text file
+    pub fn save(&self, filename: &str) -> Result<(), Error> {
+        let fh = File::create(filename)?;
+        let mut fh = BufWriter::new(fh);
+        self.write_text(&mut fh)?;
+        fh.flush()?;
+        Ok(())
+    }
+}
+
+impl PartialEq for SymbolTable {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.symbols == rhs.symbols
+    }
+}
+
+/// Creates an unnamed table from a list of symbols labelled
+/// consecutively from zero. Empty strings are left out so that they
+/// can be used to skip labels.
+impl FromIterator<String> for SymbolTable {
+    fn from_iter<T: IntoIterator<Item=String>>(iter: T) -> Self {
+        let mut symtab = SymbolTable::new("");
+        for (label, sym) in iter.into_iter().enumerate() {
+            if !sym.is_empty() {
+                symtab.add_symbol_with_label(&sym, label);
+            }
+        }
+        symtab
+    }
+}
@@ -31,171 +31,67 @@ extern crate rand;
 use rand::{SeedableRng};
 use rand::rngs::{StdRng};
 
-use std::fmt::Debug;
-
 extern crate wfst;
-use wfst::semiring::test::{RandomWeight, RandomWeightGenerator};
-use wfst::semiring::*;
+use wfst::{MutableFst, Label};
+use wfst::semiring::Weight;
+use wfst::semiring::test::{check_commutative_semiring, check_path_semiring, check_left_semiring, check_right_semiring, check_idempotent};
 use wfst::semiring::floatweight::*;
-
-extern crate serde;
-use serde::Serialize;
-use serde::de::DeserializeOwned;
-extern crate bincode;
-use bincode::{serialize, deserialize};
-
-// Tests (plus, times, zero, one) defines a commutative semiring.
-fn test_semiring1<T: Weight + Semiring + Commutative>(w1: &T, w2: &T, w3: &T) {
-    // Checks that the operations are closed.
-    assert!(w1.plus(w2).is_member());
-    assert!(w1.times(w2).is_member());
-
-    // Checks that the operations are associative.
-    assert!(w1.plus(&w2.plus(w3)).approx_eq(&w1.plus(w2).plus(w3), None));
-    assert!(w1.times(&w2.times(w3)).approx_eq(&w1.times(w2).times(w3), None));
-
-    // Checks the identity elements.
-    assert!(w1.plus(&T::zero()).eq(w1));
-    assert!(T::zero().plus(w1).eq(w1));
-    assert!(w1.times(&T::one()).eq(w1));
-    assert!(T::one().times(w1).eq(w1));
-
-    // Check the no weight element.
-    assert!(!T::none().is_member());
-    assert!(!w1.plus(&T::none()).is_member());
-    assert!(!T::none().plus(w1).is_member());
-    assert!(!w1.times(&T::none()).is_member());
-    assert!(!T::none().times(w1).is_member());
-
-    // Checks that the operations commute.
-    assert!(w1.plus(w2).approx_eq(&w2.plus(w1), None));
-    assert!(w1.times(w2).approx_eq(&w2.times(w1), None));
-
-    // Checks zero() is the annihilator.
-    assert!(w1.times(&T::zero()).eq(&T::zero()));
-    assert!(T::zero().times(w1).eq(&T::zero()));
-    
-    // Check power(w, 0) is one()
-    assert!(power(w1, 0).eq(&T::one()));
-
-    // Check power(w, 1) is w
-    assert!(power(w1, 1).eq(w1));
-
-    // Check power(w, 3) is times(w, times(w, w))
-    assert!(power(w1, 3).eq(&w1.times(&w1.times(w1))));
-
-    // Checks distributivity.
-    assert!(w1.times(&w2.plus(w3)).approx_eq(&w1.times(w2).plus(&w1.times(w3)), None)); //LeftSemiring
-    assert!(w1.plus(w2).times(w3).approx_eq(&w1.times(w3).plus(&w2.times(w3)), None)); //RightSemiring
+use wfst::semiring::booleanweight::BooleanWeight;
+use wfst::semiring::productweight::ProductWeight;
+use wfst::semiring::lexicographicweight::LexicographicWeight;
+use wfst::semiring::powerweight::{PowerWeight, SparsePowerWeight};
+use wfst::semiring::expectationweight::ExpectationWeight;
+use wfst::semiring::latticeweight::LatticeWeight;
+use wfst::semiring::stringweight::{StringWeight, StringLeft, StringRight, GallicWeight};
+use wfst::algorithms::arcmap::{arc_map, ArcMapper};
+use wfst::algorithms::shortestpath::shortest_distance;
+use wfst::wfst_vec::VecFst;
+
+type Expectation = ExpectationWeight<LogWeight<f64>, LogWeight<f64>>;
+
+// Maps probabilities `p` to `(p, p·x)` where `x` is the number of times
+// the arc's input label is `label`
+struct LabelCountMapper {
+    label: Label,
 }
 
-fn test_semiring2<T: Weight + Idempotent + Path>(w1: &T, w2: &T) {
-    assert!(w1.plus(w1).eq(w1)); //Idempotent
-    assert!(w1.plus(w2).eq(w1) || w1.plus(w2).eq(w2)); //Path
-}
-
-// Tests division operations
-fn test_division<T: Weight + Semiring + Commutative>(w1: &T, w2: &T) {
-    let p = w1.times(w2);
-
-    //LeftSemiring
-    let d = p.divide(w1, Some(DivideType::Divleft));
-    if d.is_member() {
-        assert!(p.approx_eq(&w1.times(&d), None));
-    }
-    assert!(!w1.divide(&T::none(), Some(DivideType::Divleft)).is_member());
-    assert!(!T::none().divide(w1, Some(DivideType::Divleft)).is_member());
-
-    //RightSemiring
-    let d = p.divide(w2, Some(DivideType::Divright));
-    if d.is_member() {
-        assert!(p.approx_eq(&d.times(w2), None));
+impl ArcMapper<LogWeight<f64>, Expectation> for LabelCountMapper {
+    fn map_weight(&self, weight: &LogWeight<f64>) -> Expectation {
+        ExpectationWeight::new(weight.clone(), LogWeight::zero())
     }
-    assert!(!w1.divide(&T::none(), Some(DivideType::Divright)).is_member());
-    assert!(!T::none().divide(w1, Some(DivideType::Divright)).is_member());
 
-    //Commutative
-    let d = p.divide(w1, Some(DivideType::Divright));
-    if d.is_member() {
-        assert!(p.approx_eq(&d.times(w1), None));
+    fn map_arc(&self, ilabel: Label, olabel: Label, weight: &LogWeight<f64>) -> (Label, Label, Expectation) {
+        let value = if ilabel == self.label { weight.clone() } else { LogWeight::zero() };
+        (ilabel, olabel, ExpectationWeight::new(weight.clone(), value))
     }
 }
 
-fn test_reverse<T: Weight>(w1: &T, w2: &T) {
-    let rw1 = w1.reverse();
-    let rw2 = w2.reverse();
-
-    assert!(rw1.reverse().eq(w1)); 
-    assert!(w1.plus(w2).reverse().eq(&rw1.plus(&rw2)));
-    assert!(w1.times(w2).reverse().eq(&rw1.times(&rw2)));
-}
-
-// Tests eq() is an equivalence relation.
-fn test_equality<T: Weight>(w1: &T, w2: &T, w3: &T) {
-    // Checks reflexivity.
-    assert!(w1.eq(w1));
-
-    // Checks symmetry.
-    assert!(w1.eq(w2) == w2.eq(w1));
-
-    // Checks transitivity.
-    if w1.eq(w2) && w2.eq(w3) {
-        assert!(w1.eq(w3));
+// Checks that the shortest distance over expectation weights gives the
+// total probability and expected count of label 1 from each state of
+// a small acyclic Fst, computed by hand from its paths
+fn check_expectation_distance() {
+    let prob = |p: f64| LogWeight::new(Some(-p.ln()));
+    let mut fst: VecFst<LogWeight<f64>> = VecFst::new();
+    let s0 = fst.add_state(LogWeight::zero());
+    let s1 = fst.add_state(prob(0.5));
+    let s2 = fst.add_state(LogWeight::one());
+    fst.set_start(s0);
+    fst.add_arc(s0, s1, 1, 1, prob(0.5));
+    fst.add_arc(s0, s1, 2, 2, prob(0.25));
+    fst.add_arc(s1, s2, 1, 1, prob(0.4));
+    fst.add_arc(s1, s2, 2, 2, prob(0.6));
+
+    // From state 0 the paths are 1 1 (0.2), 1 2 (0.3), 2 1 (0.1),
+    // 2 2 (0.15), 1 (0.25) and 2 (0.125)
+    let expected = [(1.125, 0.4 + 0.3 + 0.1 + 0.25), (1.5, 0.4), (1.0, 0.0)];
+    let efst: VecFst<Expectation> = arc_map(&fst, &LabelCountMapper{label: 1});
+    let d = shortest_distance(efst);
+    for (s, &(total, count)) in expected.iter().enumerate() {
+        let found = ExpectationWeight::new(prob(total), prob(count));
+        assert!(d[s].approx_eq(&found, None), "expected {:?}", found);
     }
 }
 
-fn test_io<T: Weight + Serialize + DeserializeOwned>(w1: &T) {
-    let encoded = serialize(w1).unwrap();
-    let ww = deserialize(&encoded).unwrap();
-    assert!(w1.eq(&ww));
-}
-
-fn test_clone<T: Weight>(w1: &T) {
-    let ww: T = w1.clone();
-    assert!(w1.eq(&ww));
-}
-
-// Test a variety of identities and properties that must hold for the
-// Weight implementation to be well-defined.  Note in the tests we use
-// approx_eq() rather than == where the weights might be inexact.
-fn test12<T: RandomWeight + Semiring + Commutative + Idempotent + Path + Debug + Serialize + DeserializeOwned>(rng: &mut StdRng,
-                                                                                                               n_iterations: u32,
-                                                                                                               test_div: bool) {
-    for _ in 0..n_iterations {
-        let w1 = rng.genweight::<T>(true);
-        let w2 = rng.genweight::<T>(true);
-        let w3 = rng.genweight::<T>(true);
-        test_semiring1(&w1, &w2, &w3);
-        test_semiring2(&w1, &w2);
-        if test_div {
-            test_division(&w1, &w2);
-        }
-        test_reverse(&w1, &w2);
-        test_equality(&w1, &w2, &w3);
-        test_io(&w1);
-        test_clone(&w1);
-    }
-}
-
-fn test1<T: RandomWeight + Semiring + Commutative + Debug + Serialize + DeserializeOwned>(rng: &mut StdRng,
-                                                                                          n_iterations: u32,
-                                                                                          test_div: bool) {
-    for _ in 0..n_iterations {
-        let w1 = rng.genweight::<T>(true);
-        let w2 = rng.genweight::<T>(true);
-        let w3 = rng.genweight::<T>(true);
-        test_semiring1(&w1, &w2, &w3);
-        if test_div {
-            test_division(&w1, &w2);
-        }
-        test_reverse(&w1, &w2);
-        test_equality(&w1, &w2, &w3);
-        test_io(&w1);
-        test_clone(&w1);
-    }
-}
-    
-
 fn main() {
     let seed: u64 = 777;
     let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
@@ -206,23 +102,77 @@ fn main() {
     println!("============================================================\n");
     //f32
     println!("Testing `TropicalWeight<f32>`:");
-    test12::<TropicalWeight<f32>>(&mut rng, n_iterations, true);
+    check_path_semiring::<TropicalWeight<f32>>(&mut rng, n_iterations, true);
     println!("============================================================\n");
     println!("Testing `LogWeight<f32>`:");
-    test1::<LogWeight<f32>>(&mut rng, n_iterations, true);
+    check_commutative_semiring::<LogWeight<f32>>(&mut rng, n_iterations, true);
     println!("============================================================\n");
     println!("Testing `MinmaxWeight<f32>`:");
-    test12::<MinmaxWeight<f32>>(&mut rng, n_iterations, true);
+    check_path_semiring::<MinmaxWeight<f32>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `SignedLogWeight<f32>`:");
+    check_commutative_semiring::<SignedLogWeight<f32>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `RealWeight<f32>`:");
+    check_commutative_semiring::<RealWeight<f32>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `ArcticWeight<f32>`:");
+    check_path_semiring::<ArcticWeight<f32>>(&mut rng, n_iterations, true);
     println!("============================================================\n");
     //f64
     println!("Testing `TropicalWeight<f64>`:");
-    test12::<TropicalWeight<f64>>(&mut rng, n_iterations, true);
+    check_path_semiring::<TropicalWeight<f64>>(&mut rng, n_iterations, true);
     println!("============================================================\n");
     println!("Testing `LogWeight<f64>`:");
-    test1::<LogWeight<f64>>(&mut rng, n_iterations, true);
+    check_commutative_semiring::<LogWeight<f64>>(&mut rng, n_iterations, true);
     println!("============================================================\n");
     println!("Testing `MinmaxWeight<f64>`:");
-    test12::<MinmaxWeight<f64>>(&mut rng, n_iterations, true);
+    check_path_semiring::<MinmaxWeight<f64>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `SignedLogWeight<f64>`:");
+    check_commutative_semiring::<SignedLogWeight<f64>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `RealWeight<f64>`:");
+    check_commutative_semiring::<RealWeight<f64>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `ArcticWeight<f64>`:");
+    check_path_semiring::<ArcticWeight<f64>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    //boolean
+    println!("Testing `BooleanWeight`:");
+    check_path_semiring::<BooleanWeight>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    //composite
+    println!("Testing `LatticeWeight<f32>`:");
+    check_path_semiring::<LatticeWeight<f32>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `ProductWeight<TropicalWeight<f32>, LogWeight<f32>>`:");
+    check_commutative_semiring::<ProductWeight<TropicalWeight<f32>, LogWeight<f32>>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `LexicographicWeight<TropicalWeight<f32>, TropicalWeight<f32>>`:");
+    check_path_semiring::<LexicographicWeight<TropicalWeight<f32>, TropicalWeight<f32>>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `PowerWeight<TropicalWeight<f32>, 3>`:");
+    check_commutative_semiring::<PowerWeight<TropicalWeight<f32>, 3>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `SparsePowerWeight<LogWeight<f32>>`:");
+    check_commutative_semiring::<SparsePowerWeight<LogWeight<f32>>>(&mut rng, n_iterations, true);
+    println!("============================================================\n");
+    println!("Testing `ExpectationWeight<LogWeight<f64>, LogWeight<f64>>`:");
+    check_commutative_semiring::<ExpectationWeight<LogWeight<f64>, LogWeight<f64>>>(&mut rng, n_iterations, false);
+    check_expectation_distance();
+    println!("============================================================\n");
+    println!("Testing `StringWeight<StringLeft>`:");
+    check_left_semiring::<StringWeight<StringLeft>>(&mut rng, n_iterations, true);
+    check_idempotent::<StringWeight<StringLeft>>(&mut rng, n_iterations);
+    println!("============================================================\n");
+    println!("Testing `StringWeight<StringRight>`:");
+    check_right_semiring::<StringWeight<StringRight>>(&mut rng, n_iterations, true);
+    check_idempotent::<StringWeight<StringRight>>(&mut rng, n_iterations);
+    println!("============================================================\n");
+    println!("Testing `GallicWeight<TropicalWeight<f32>>`:");
+    check_left_semiring::<GallicWeight<TropicalWeight<f32>>>(&mut rng, n_iterations, true);
+    check_idempotent::<GallicWeight<TropicalWeight<f32>>>(&mut rng, n_iterations);
     println!("============================================================\n");
     
     println!("TESTS PASSED");
@@ -0,0 +1,501 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! Checks reading and writing of the supported file formats against
+//! the reference files in `refdata`, panicking if any check fails.
+
+extern crate wfst;
+use wfst::{Fst, ExpandedFst, Arc, StateId, Label};
+use wfst::semiring::Weight;
+use wfst::semiring::floatweight::{TropicalWeight, LogWeight};
+use wfst::semiring::latticeweight::LatticeWeight;
+use wfst::symboltable::SymbolTable;
+use wfst::wfst_vec::VecFst;
+use wfst::wfst_io;
+use wfst::wfst_io::openfst::{self, OpenFstWeight, OpenFstType};
+use wfst::wfst_io::arpa::{read_arpa, ArpaBackoff, PHI_SYMBOL};
+use wfst::wfst_io::archive::{ArchiveReader, ArchiveWriter};
+use wfst::wfst_io::compression::{Compression, CompressedWriter, decompress, peek};
+use wfst::wfst_io::json::{read_json, write_json};
+use wfst::wfst_io::kaldi::{read_kaldi_lattices, write_kaldi_lattice, KaldiLatticeType};
+use wfst::wfst_io::slf::{read_slf, write_slf};
+use wfst::wfst_io::text::{read_att, write_att, split_text_archive};
+use wfst::Error;
+
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::rc::Rc;
+
+fn refdata(filename: &str) -> String {
+    format!("{}/refdata/{}", env!("CARGO_MANIFEST_DIR"), filename)
+}
+
+fn open(filename: &str) -> BufReader<File> {
+    BufReader::new(File::open(refdata(filename)).unwrap())
+}
+
+fn att<W: Weight + Display, F: ExpandedFst<W>>(fst: &F) -> String {
+    let mut buf = Vec::new();
+    write_att(fst, &mut buf, false).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+fn symtab(filename: &str) -> Rc<SymbolTable> {
+    Rc::new(SymbolTable::read_text(filename, open(filename)).unwrap())
+}
+
+// Panics unless `result` is a format error at line `line`
+fn assert_format_error<T>(result: Result<T, Error>, line: Option<usize>) {
+    match result {
+        Err(Error::Format{line: l, ..}) => assert_eq!(l, line),
+        Err(e) => panic!("expected a format error, got: {}", e),
+        Ok(_) => panic!("expected a format error"),
+    }
+}
+
+// AT&T text files and text archives
+fn check_text() {
+    let text = std::fs::read_to_string(refdata("fst_int.txt")).unwrap();
+    let fst: VecFst<TropicalWeight<f32>> = read_att(text.as_bytes(), None, None, false).unwrap();
+    assert_eq!(fst.get_numstates(), 3);
+    assert_eq!(fst.get_start(), Some(0));
+    let written = att(&fst);
+    let again: VecFst<TropicalWeight<f32>> = read_att(written.as_bytes(), None, None, false).unwrap();
+    assert_eq!(att(&again), written);
+
+    let entries = split_text_archive(&std::fs::read_to_string(refdata("archive.txt")).unwrap());
+    assert_eq!(entries.iter().map(|e| e.0.as_str()).collect::<Vec<_>>(), vec!["first", "second"]);
+    assert_eq!(entries[0].1, text);
+    let acceptor: VecFst<TropicalWeight<f32>> = read_att(entries[1].1.as_bytes(), None, None, true).unwrap();
+    let mut buf = Vec::new();
+    write_att(&acceptor, &mut buf, true).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), "0\t1\t3\n1\t2\t4\t0.25\n2\n");
+
+    // Errors give the line number
+    assert_format_error(read_att::<TropicalWeight<f32>, VecFst<_>, _>(open("fst_bad.txt"), None, None, false), Some(3));
+    assert_format_error(read_att::<TropicalWeight<f32>, VecFst<_>, _>(open("fst_int.txt"), Some(symtab("isyms.txt")), None, false), Some(1));
+    assert_format_error(read_att::<TropicalWeight<f32>, VecFst<_>, _>(&b"0 1 1 1\n1 x\n"[..], None, None, false), Some(2));
+    // State ids far beyond the size of the file
+    assert_format_error(read_att::<TropicalWeight<f32>, VecFst<_>, _>(&b"0 1 1 1\n0 99999999999 1 1\n"[..], None, None, false), Some(2));
+}
+
+// JSON files
+fn check_json() {
+    let fst: VecFst<TropicalWeight<f32>> = read_json(open("fst_str.json")).unwrap();
+    let expected: VecFst<TropicalWeight<f32>> = read_att(open("fst_str.txt"), Some(symtab("isyms.txt")), Some(symtab("osyms.txt")), false).unwrap();
+    assert_eq!(att(&fst), att(&expected));
+    assert_eq!(fst.get_isyms().unwrap().checksum(), symtab("isyms.txt").checksum());
+    assert_eq!(fst.get_osyms().unwrap().checksum(), symtab("osyms.txt").checksum());
+    let mut buf = Vec::new();
+    write_json(&fst, &mut buf).unwrap();
+    assert_eq!(buf, std::fs::read(refdata("fst_str.json")).unwrap());
+
+    // Omitted weights are one, omitted final weights zero
+    let fst: VecFst<TropicalWeight<f32>> = read_json(open("fst_defaults.json")).unwrap();
+    assert_eq!(att(&fst), "0\t1\t1\t2\n1\n");
+
+    // The weight type must match
+    match read_json::<LogWeight<f32>, VecFst<_>, _>(open("fst_str.json")) {
+        Err(Error::UnsupportedWeight(wtype)) => assert_eq!(wtype, TropicalWeight::<f32>::wtype()),
+        _ => panic!("expected an unsupported weight error"),
+    }
+    assert_format_error(read_json::<TropicalWeight<f32>, VecFst<_>, _>(&br#"{"wtype": "tropical32", "start": 0, "states": [{"arcs": [{"ilabel": 1, "olabel": 1, "nextstate": 1}]}]}"#[..]), None);
+}
+
+// Archives, with the entries of `archive.txt` (the second as an
+// acceptor with 64-bit log weights)
+fn check_archive() {
+    let entries = split_text_archive(&std::fs::read_to_string(refdata("archive.txt")).unwrap());
+    let first: VecFst<TropicalWeight<f32>> = read_att(entries[0].1.as_bytes(), None, None, false).unwrap();
+    let second: VecFst<LogWeight<f64>> = read_att(entries[1].1.as_bytes(), None, None, true).unwrap();
+
+    let mut archive = ArchiveReader::open(&refdata("archive.far")).unwrap();
+    assert_eq!(archive.version(), 1);
+    assert_eq!(archive.keys().collect::<Vec<_>>(), vec!["first", "second"]);
+    assert!(archive.contains_key("second") && !archive.contains_key("third"));
+    let fst: VecFst<TropicalWeight<f32>> = archive.get("first").unwrap();
+    assert_eq!(att(&fst), att(&first));
+    let fst: VecFst<LogWeight<f64>> = archive.get("second").unwrap();
+    assert_eq!(att(&fst), att(&second));
+    let wtypes = archive.iter().map(|r| r.unwrap()).map(|(key, wrapped)| (key, wrapped.wtype)).collect::<Vec<_>>();
+    assert_eq!(wtypes, vec![(String::from("first"), TropicalWeight::<f32>::wtype()),
+                            (String::from("second"), LogWeight::<f64>::wtype())]);
+
+    // Wrong types and missing keys
+    match archive.get::<TropicalWeight<f32>, VecFst<_>>("second") {
+        Err(Error::Entry{key, error}) => {
+            assert_eq!(key, "second");
+            assert!(matches!(*error, Error::UnsupportedType{..}));
+        },
+        _ => panic!("expected an unsupported type error"),
+    }
+    assert!(matches!(archive.get::<TropicalWeight<f32>, VecFst<_>>("third"), Err(Error::KeyNotFound(_))));
+
+    // Writing gives the same bytes, keys must be unique
+    let d = std::fs::read(refdata("archive.far")).unwrap();
+    let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+    writer.add("first", &first).unwrap();
+    writer.add("second", &second).unwrap();
+    assert!(matches!(writer.add("first", &first), Err(Error::DuplicateKey(_))));
+    assert_eq!(writer.finish().unwrap(), d);
+
+    // Incomplete archives are errors
+    assert_format_error(ArchiveReader::new(Cursor::new(&d[..d.len() - 1])), None);
+}
+
+// Kaldi lattice text archives, in both forms
+fn check_kaldi() {
+    let lattices: Vec<(String, VecFst<LatticeWeight<f32>>)> = read_kaldi_lattices(open("lattices.txt"), KaldiLatticeType::Lattice, Some(symtab("osyms.txt"))).unwrap();
+    assert_eq!(lattices.iter().map(|l| l.0.as_str()).collect::<Vec<_>>(), vec!["utt1", "utt2"]);
+    assert!(lattices[0].1.get_isyms().is_none());
+    assert_eq!(lattices[0].1.get_osyms().unwrap().checksum(), symtab("osyms.txt").checksum());
+    let mut buf = Vec::new();
+    for (key, fst) in &lattices {
+        write_kaldi_lattice(key, fst, KaldiLatticeType::Lattice, &mut buf).unwrap();
+    }
+    assert_eq!(buf, std::fs::read(refdata("lattices.txt")).unwrap());
+
+    // Transition ids are discarded from compact lattices, which are
+    // written alignment-free
+    let compact: Vec<(String, VecFst<LatticeWeight<f32>>)> = read_kaldi_lattices(open("lattices_compact.txt"), KaldiLatticeType::Compact, None).unwrap();
+    assert_eq!(compact.iter().map(|l| l.0.as_str()).collect::<Vec<_>>(), vec!["utt1", "utt2"]);
+    let mut buf = Vec::new();
+    write_kaldi_lattice("utt1", &compact[0].1, KaldiLatticeType::Compact, &mut buf).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), "utt1 \n0\t1\t1\t1.5,2.25,\n0\t1\t2\t0.5,3,\n1\t2\t0\t0,1.5,\n2\t0,0.5,\n\n");
+    let mut buf = Vec::new();
+    write_kaldi_lattice("utt1", &lattices[0].1, KaldiLatticeType::Compact, &mut buf).unwrap();
+    let mut expected = Vec::new();
+    write_kaldi_lattice("utt1", &compact[0].1, KaldiLatticeType::Compact, &mut expected).unwrap();
+    assert_eq!(buf, expected);
+    assert_eq!(att(&compact[1].1), "0\t1\t3\t3\n1\n");
+}
+
+// The arcs of a lattice with their graph and acoustic costs
+fn lattice_arcs<F: ExpandedFst<LatticeWeight<f64>>>(fst: &F) -> Vec<(StateId, StateId, Label, f64, f64)> {
+    (0..fst.get_numstates()).flat_map(|i| {
+        fst.arc_iter(i).map(move |arc| {
+            let (g, a) = arc.weight().value().unwrap();
+            (i, arc.nextstate(), arc.olabel(), g, a)
+        }).collect::<Vec<_>>()
+    }).collect()
+}
+
+fn assert_lattice_arcs(arcs: &[(StateId, StateId, Label, f64, f64)], expected: &[(StateId, StateId, Label, f64, f64)]) {
+    assert_eq!(arcs.len(), expected.len());
+    for (arc, e) in arcs.iter().zip(expected) {
+        assert_eq!((arc.0, arc.1, arc.2), (e.0, e.1, e.2));
+        assert!((arc.3 - e.3).abs() < 1e-9 && (arc.4 - e.4).abs() < 1e-9, "{:?} != {:?}", arc, e);
+    }
+}
+
+// HTK SLF lattices, with words on nodes and links and base 10 scores
+fn check_slf() {
+    let ln10 = 10f64.ln();
+    let expected = [(0, 1, 1, 1.5 * ln10, 2.25 * ln10),
+                    (0, 2, 2, 0.5 * ln10, 3.0 * ln10),
+                    (1, 3, 0, 0.0, 1.5 * ln10),
+                    (2, 3, 3, 0.25 * ln10, 1.0 * ln10)];
+    let fst: VecFst<LatticeWeight<f64>> = read_slf(open("lattice.slf"), Some(symtab("osyms.txt"))).unwrap();
+    assert_eq!(fst.get_start(), Some(0));
+    assert_eq!((0..4).filter(|i| fst.is_final(*i)).collect::<Vec<_>>(), vec![3]);
+    assert_lattice_arcs(&lattice_arcs(&fst), &expected);
+
+    // Without a symbol table, words are added as they are found
+    let fst: VecFst<LatticeWeight<f64>> = read_slf(open("lattice.slf"), None).unwrap();
+    assert_lattice_arcs(&lattice_arcs(&fst), &expected);
+    let words = fst.get_osyms().unwrap();
+    assert_eq!((0..4).map(|l| words.find_symbol(l).unwrap()).collect::<Vec<_>>(), vec!["<eps>", "x", "y", "人"]);
+
+    // Written with natural log scores
+    let mut buf = Vec::new();
+    write_slf(&fst, &mut buf, Some("utt1")).unwrap();
+    let again: VecFst<LatticeWeight<f64>> = read_slf(&buf[..], None).unwrap();
+    assert_lattice_arcs(&lattice_arcs(&again), &expected);
+
+    // Words must be in the symbol table
+    assert_format_error(read_slf::<f64, VecFst<_>, _>(open("lattice.slf"), Some(symtab("isyms.txt"))), Some(10));
+    // Node ids far beyond the size of the file
+    assert_format_error(read_slf::<f64, VecFst<_>, _>(&b"N=99999999999 L=0\n"[..], None), Some(1));
+}
+
+// ARPA language models: `lm.arpa` is a trigram model with histories
+// <s> a b c (states 1-4), "<s> a", "a b" and "b c" (states 5-7)
+fn check_arpa() {
+    let fst: VecFst<TropicalWeight<f64>> = read_arpa(open("lm.arpa"), None, ArpaBackoff::Epsilon).unwrap();
+    let words = fst.get_isyms().unwrap();
+    assert_eq!((0..6).map(|l| words.find_symbol(l).unwrap()).collect::<Vec<_>>(), vec!["<eps>", "</s>", "<s>", "a", "b", "c"]);
+    let (a, b, c) = (3, 4, 5);
+    // The start state is the state of <s>, </s> probabilities are final
+    // weights and n-grams lead to the longest suffix that is a history
+    assert_eq!(fst.get_start(), Some(1));
+    let expected = [(0, 2, a, 0.5), (0, 3, b, 0.75), (0, 4, c, 1.5),
+                    (1, 0, 0, 0.5), (1, 5, a, 0.3),
+                    (2, 0, 0, 0.25), (2, 6, b, 0.4),
+                    (3, 0, 0, 0.2), (3, 7, c, 0.5),
+                    (4, 0, 0, 0.0),
+                    (5, 2, 0, 0.1), (5, 6, b, 0.1),
+                    (6, 3, 0, 0.15),
+                    (7, 4, 0, 0.0)];
+    let arcs = (0..fst.get_numstates()).flat_map(|i| {
+        fst.arc_iter(i).map(move |arc| (i, arc.nextstate(), arc.ilabel(), arc.weight().value().unwrap())).collect::<Vec<_>>()
+    }).collect::<Vec<_>>();
+    assert_eq!(arcs.len(), expected.len());
+    for (arc, e) in arcs.iter().zip(&expected) {
+        assert_eq!((arc.0, arc.1, arc.2), (e.0, e.1, e.2));
+        assert!((arc.3 - e.3 * 10f64.ln()).abs() < 1e-9, "{:?} != {:?}", arc, e);
+    }
+    let finals = (0..fst.get_numstates()).filter(|i| fst.is_final(*i))
+        .map(|i| (i, fst.get_finalweight(i).value().unwrap() / 10f64.ln())).collect::<Vec<_>>();
+    assert_eq!(finals.iter().map(|f| f.0).collect::<Vec<_>>(), vec![0, 2, 3, 6]);
+    for (f, e) in finals.iter().zip(&[1.0, 0.6, 0.2, 0.05]) {
+        assert!((f.1 - e).abs() < 1e-9);
+    }
+
+    // Failure back-off arcs
+    let fst: VecFst<TropicalWeight<f64>> = read_arpa(open("lm.arpa"), None, ArpaBackoff::Failure).unwrap();
+    let phi = fst.get_isyms().unwrap().find_label(PHI_SYMBOL).unwrap();
+    assert_eq!(fst.arc_iter(6).map(|arc| arc.ilabel()).collect::<Vec<_>>(), vec![phi]);
+
+    // The counts must match
+    let text = std::fs::read_to_string(refdata("lm.arpa")).unwrap().replace("ngram 3=2", "ngram 3=3");
+    assert_format_error(read_arpa::<f64, VecFst<_>, _>(text.as_bytes(), None, ArpaBackoff::Epsilon), None);
+    let text = std::fs::read_to_string(refdata("lm.arpa")).unwrap().replace("ngram 1=5", "ngram 1=99999999999999");
+    assert_format_error(read_arpa::<f64, VecFst<_>, _>(text.as_bytes(), None, ArpaBackoff::Epsilon), None);
+    assert_format_error(read_arpa::<f64, VecFst<_>, _>(open("lm.arpa"), Some(symtab("isyms.txt")), ArpaBackoff::Epsilon), Some(7));
+}
+
+// Compressed files are detected and decompressed
+fn check_compression() {
+    let text = std::fs::read(refdata("fst_int.txt")).unwrap();
+    let gzipped = std::fs::read(refdata("fst_int.txt.gz")).unwrap();
+    let zstded = std::fs::read(refdata("fst_int.txt.zst")).unwrap();
+    assert_eq!(Compression::detect(&text), Compression::None);
+    assert_eq!(Compression::detect(&gzipped), Compression::Gzip);
+    assert_eq!(Compression::detect(&zstded), Compression::Zstd);
+
+    let mut buf = Vec::new();
+    decompress(&text[..]).unwrap().read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, text);
+    let mut buf = Vec::new();
+    decompress(&gzipped[..]).unwrap().read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, text);
+    let fst: VecFst<TropicalWeight<f32>> = read_att(decompress(open("fst_int.txt.gz")).unwrap(), None, None, false).unwrap();
+    let expected: VecFst<TropicalWeight<f32>> = read_att(&text[..], None, None, false).unwrap();
+    assert_eq!(att(&fst), att(&expected));
+    if cfg!(feature = "zstd") {
+        let mut buf = Vec::new();
+        decompress(&zstded[..]).unwrap().read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, text);
+    } else {
+        assert!(matches!(decompress(&zstded[..]), Err(Error::Unsupported(_))));
+    }
+
+    // Compressed native files, as written by `wfstcompile -z gzip`
+    let compressed: VecFst<TropicalWeight<f32>> = wfst_io::load(&refdata("fst_int.fst.gz")).unwrap();
+    assert_eq!(att(&compressed), att(&fst));
+
+    // Writing and reading back
+    let mut compressions = vec![Compression::None, Compression::Gzip];
+    if cfg!(feature = "zstd") {
+        compressions.push(Compression::Zstd);
+    }
+    for compression in compressions {
+        let mut writer = CompressedWriter::new(Vec::new(), compression).unwrap();
+        writer.write_all(&text).unwrap();
+        let d = writer.finish().unwrap();
+        assert_eq!(Compression::detect(&d), compression);
+        let mut buf = Vec::new();
+        decompress(&d[..]).unwrap().read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, text);
+    }
+
+    // Input arriving a byte at a time (as from a pipe) is still detected
+    let mut buf = Vec::new();
+    decompress(BufReader::with_capacity(1, &gzipped[..])).unwrap().read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, text);
+    let mut reader = peek(BufReader::with_capacity(1, &gzipped[..]), 4).unwrap();
+    assert_eq!(reader.fill_buf().unwrap(), &gzipped[..4]);
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, gzipped);
+    // Shorter input is returned in full
+    assert_eq!(peek(&text[..2], 4).unwrap().fill_buf().unwrap(), &text[..2]);
+}
+
+// Corrupt and truncated native files are errors
+fn check_native() {
+    let fst: VecFst<TropicalWeight<f32>> = read_att(open("fst_int.txt"), None, None, false).unwrap();
+    let d = wfst_io::serialise(&fst).unwrap();
+    let again: VecFst<TropicalWeight<f32>> = wfst_io::deserialise_from(&d[..]).unwrap();
+    assert_eq!(att(&again), att(&fst));
+    // Within the header, and within the Fst
+    for n in &[6, 12, 30] {
+        assert_format_error(wfst_io::deserialise_wrapper(&d[..*n]), None);
+    }
+    for n in &[6, 12, 30, d.len() - 1] {
+        assert_format_error(wfst_io::deserialise_from::<TropicalWeight<f32>, VecFst<_>, _>(&d[..*n]), None);
+    }
+
+    // A header claiming a huge type name
+    let mut corrupt = b"WFST\x01\0\0\0".to_vec();
+    corrupt.extend_from_slice(&0x7fff_ffff_ffff_fff0u64.to_le_bytes());
+    corrupt.extend_from_slice(&[0; 4]);
+    assert_format_error(wfst_io::deserialise_wrapper(&corrupt), None);
+    assert_format_error(wfst_io::deserialise_from::<TropicalWeight<f32>, VecFst<_>, _>(&corrupt[..]), None);
+
+    // An archive record claiming a huge key
+    let mut corrupt = std::fs::read(refdata("archive.far")).unwrap();
+    corrupt[8..16].copy_from_slice(&0x7fff_ffff_ffff_fff0u64.to_le_bytes());
+    let mut archive = ArchiveReader::new(Cursor::new(&corrupt)).unwrap();
+    assert_format_error(archive.get::<TropicalWeight<f32>, VecFst<_>>("first"), None);
+}
+
+// Native files written by `wfstcompile` before format version 1
+fn check_legacy() {
+    let expected: VecFst<TropicalWeight<f32>> = read_att(open("fst_int.txt"), None, None, false).unwrap();
+
+    let fst: VecFst<TropicalWeight<f32>> = wfst_io::load(&refdata("legacy_tropical.fst")).unwrap();
+    assert_eq!(att(&fst), att(&expected));
+
+    let fst: VecFst<LogWeight<f64>> = wfst_io::load(&refdata("legacy_log64.fst")).unwrap();
+    assert_eq!(att(&fst), att(&expected));
+
+    // The weight type must be named, and the layout must match
+    let d = std::fs::read(refdata("legacy_log64.fst")).unwrap();
+    assert!(matches!(wfst_io::deserialise_wrapper(&d), Err(Error::LegacyFile)));
+    assert!(matches!(wfst_io::deserialise_wrapper_from(&d[..]), Err(Error::LegacyFile)));
+    assert_format_error(wfst_io::deserialise::<LogWeight<f32>, VecFst<_>>(&d), None);
+
+    let fst: VecFst<TropicalWeight<f32>> = wfst_io::load(&refdata("legacy_syms.fst")).unwrap();
+    let expected: VecFst<TropicalWeight<f32>> = read_att(open("fst_str.txt"), Some(symtab("isyms.txt")), Some(symtab("osyms.txt")), false).unwrap();
+    assert_eq!(att(&fst), att(&expected));
+    assert_eq!(fst.get_isyms().unwrap().checksum(), symtab("isyms.txt").checksum());
+    assert_eq!(fst.get_osyms().unwrap().checksum(), symtab("osyms.txt").checksum());
+}
+
+// Reads an OpenFst file, checks its contents against the text file
+// `txtfile` and that writing it again gives the same bytes
+fn check_openfst<W>(filename: &str, txtfile: &str, fsttype: Option<OpenFstType>, syms: bool)
+    where W: OpenFstWeight + Display
+{
+    let d = std::fs::read(refdata(filename)).unwrap();
+    let fst: VecFst<W> = openfst::read(&d).unwrap();
+    let expected: VecFst<W> = if syms {
+        read_att(open(txtfile), Some(symtab("isyms.txt")), Some(symtab("osyms.txt")), false).unwrap()
+    } else {
+        read_att(open(txtfile), None, None, false).unwrap()
+    };
+    assert_eq!(att(&fst), att(&expected));
+    assert_eq!(fst.get_isyms().is_some(), syms);
+    if let Some(fsttype) = fsttype {
+        assert_eq!(openfst::header(&d).unwrap().fsttype, fsttype.name());
+        assert_eq!(openfst::write(&fst, fsttype).unwrap(), d);
+    }
+}
+
+// The bytes of an OpenFst file with the properties in the header zeroed
+fn without_properties(d: &[u8]) -> Vec<u8> {
+    let hdr = openfst::header(d).unwrap();
+    let pos = 4 + 4 + hdr.fsttype.len() + 4 + hdr.arctype.len() + 4 + 4;
+    let mut d = d.to_vec();
+    d[pos..pos + 8].copy_from_slice(&[0; 8]);
+    d
+}
+
+// Checks a file written by OpenFst itself as `check_openfst()` does,
+// except for the properties (OpenFst writes those known at the time),
+// if it is present
+fn check_openfst_reference<W>(filename: &str, txtfile: &str, fsttype: OpenFstType, syms: bool)
+    where W: OpenFstWeight + Display
+{
+    let d = match std::fs::read(refdata(filename)) {
+        Ok(d) => d,
+        Err(_) => {
+            println!("{} not found, skipped (see `refdata/make_openfst_reference.sh`)", filename);
+            return
+        },
+    };
+    let fst: VecFst<W> = openfst::read(&d).unwrap();
+    let expected: VecFst<W> = if syms {
+        read_att(open(txtfile), Some(symtab("isyms.txt")), Some(symtab("osyms.txt")), false).unwrap()
+    } else {
+        read_att(open(txtfile), None, None, false).unwrap()
+    };
+    assert_eq!(att(&fst), att(&expected));
+    assert_eq!(fst.get_isyms().is_some(), syms);
+    assert_eq!(openfst::header(&d).unwrap().fsttype, fsttype.name());
+    assert_eq!(without_properties(&openfst::write(&fst, fsttype).unwrap()), without_properties(&d));
+}
+
+// OpenFst binary files (see `refdata/make_openfst_fixtures.py`, these
+// are not written by OpenFst itself)
+fn check_openfst_files() {
+    check_openfst::<TropicalWeight<f32>>("openfst_vector_standard.fst", "fst_int.txt", Some(OpenFstType::Vector), false);
+    check_openfst::<LogWeight<f64>>("openfst_vector_log64_syms.fst", "fst_str.txt", Some(OpenFstType::Vector), true);
+    check_openfst::<TropicalWeight<f32>>("openfst_const_standard.fst", "fst_int.txt", Some(OpenFstType::Const), false);
+    check_openfst::<TropicalWeight<f64>>("openfst_const_tropical64_syms.fst", "fst_str.txt", Some(OpenFstType::Const), true);
+    // Aligned files are read but not written
+    check_openfst::<LogWeight<f32>>("openfst_const_aligned_log.fst", "fst_int.txt", None, false);
+
+    // The arc type must match
+    let d = std::fs::read(refdata("openfst_vector_standard.fst")).unwrap();
+    assert!(openfst::read::<LogWeight<f32>, VecFst<_>>(&d).is_err());
+    // Truncated files are errors
+    assert!(openfst::read::<TropicalWeight<f32>, VecFst<_>>(&d[..d.len() - 1]).is_err());
+
+    // Files written by OpenFst (see `refdata/make_openfst_reference.sh`)
+    check_openfst_reference::<TropicalWeight<f32>>("fstcompile_vector_standard.fst", "fst_int.txt", OpenFstType::Vector, false);
+    check_openfst_reference::<LogWeight<f64>>("fstcompile_vector_log64_syms.fst", "fst_str.txt", OpenFstType::Vector, true);
+    check_openfst_reference::<TropicalWeight<f32>>("fstcompile_const_standard.fst", "fst_int.txt", OpenFstType::Const, false);
+    check_openfst_reference::<TropicalWeight<f32>>("fstcompile_const_standard_syms.fst", "fst_str.txt", OpenFstType::Const, true);
+}
+
+fn main() {
+    println!("Checking AT&T text files:");
+    check_text();
+    println!("============================================================\n");
+
+    println!("Checking JSON files:");
+    check_json();
+    println!("============================================================\n");
+
+    println!("Checking archives:");
+    check_archive();
+    println!("============================================================\n");
+
+    println!("Checking Kaldi lattices:");
+    check_kaldi();
+    println!("============================================================\n");
+
+    println!("Checking SLF lattices:");
+    check_slf();
+    println!("============================================================\n");
+
+    println!("Checking ARPA language models:");
+    check_arpa();
+    println!("============================================================\n");
+
+    println!("Checking compression:");
+    check_compression();
+    println!("============================================================\n");
+
+    println!("Checking native files:");
+    check_native();
+    println!("============================================================\n");
+
+    println!("Checking legacy native files:");
+    check_legacy();
+    println!("============================================================\n");
+
+    println!("Checking OpenFst files:");
+    check_openfst_files();
+    println!("============================================================\n");
+
+    println!("TESTS PASSED");
+}
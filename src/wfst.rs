@@ -22,6 +22,7 @@
 //!  * CMU Sphinx (http://cmusphinx.sourceforge.net/)
 
 use std::fmt::Debug;
+use std::rc::Rc;
 
 ////////////////////////////////////////////////////////////////////////////////
 ////////// SEMIRING MODULE PROVIDES WEIGHT TYPES
@@ -40,36 +41,126 @@ use semiring::Weight;
 pub type Label = usize;
 pub type StateId = usize;
 
+pub mod symboltable;
+use symboltable::SymbolTable;
+
+pub mod error;
+pub use error::Error;
+
 pub trait Fst<W: Weight>: Debug {
     type Arc: Arc<W>;
     type Iter: Iterator<Item=Self::Arc>;
-    type Symtab: IntoIterator<Item=String>;
     fn get_start(&self) -> Option<StateId>;
     fn get_finalweight(&self, StateId) -> W;
     fn arc_iter(&self, StateId) -> Self::Iter;
-    fn get_isyms(&self) -> Option<Self::Symtab>;
-    fn get_osyms(&self) -> Option<Self::Symtab>;
+    fn get_isyms(&self) -> Option<Rc<SymbolTable>>;
+    fn get_osyms(&self) -> Option<Rc<SymbolTable>>;
     fn is_final(&self, StateId) -> bool;
+    /// Name of the implementation, e.g. used to identify files
+    fn fst_type() -> String;
 }
 
 // This interface defined by looking at OpenFST (C++ and Java
 // interfaces):
 pub trait MutableFst<W: Weight>: Fst<W> {
+    type ArcMut<'a>: MutableArc<W> where Self: 'a;
+    type IterMut<'a>: Iterator<Item=Self::ArcMut<'a>> where Self: 'a;
     fn new() -> Self;
     fn set_start(&mut self, id: StateId);
     fn add_state(&mut self, finalweight: W) -> StateId;
+    /// Deletes a state and all arcs into it, renumbering the states
+    /// after it. If it is the start state, the Fst is left without a
+    /// start state.
     fn del_state(&mut self, StateId);
+    /// Deletes the given states and all arcs into them, renumbering the
+    /// remaining states in order. If the start state is deleted, the Fst
+    /// is left without a start state.
     fn del_states<T: IntoIterator<Item=StateId>>(&mut self, states: T);
     fn add_arc(&mut self, source: StateId, target: StateId, ilabel: Label, olabel: Label, weight: W);
+    fn set_arc(&mut self, source: StateId, index: usize, target: StateId, ilabel: Label, olabel: Label, weight: W);
+    fn del_arc(&mut self, source: StateId, index: usize);
+    fn del_arcs(&mut self, source: StateId);
+    /// Iterates over the arcs of `source` allowing them to be changed
+    /// in place, the arcs borrow the Fst mutably
+    fn arc_iter_mut(&mut self, source: StateId) -> Self::IterMut<'_>;
+    fn reserve_states(&mut self, n: usize);
+    fn reserve_arcs(&mut self, source: StateId, n: usize);
+    fn set_properties(&mut self, props: Properties, mask: Properties);
     fn set_finalweight(&mut self, id: StateId, finalweight: W);
-    fn set_isyms<T: IntoIterator<Item=String>>(&mut self, symtab: T);
-    fn set_osyms<T: IntoIterator<Item=String>>(&mut self, symtab: T);
+    fn set_isyms<T: Into<Rc<SymbolTable>>>(&mut self, symtab: T);
+    fn set_osyms<T: Into<Rc<SymbolTable>>>(&mut self, symtab: T);
     fn del_isyms(&mut self);
     fn del_osyms(&mut self);
+
+    // Fallible variants of the above, returning errors for invalid
+    // state ids and arc indices instead of panicking
+    fn try_set_start(&mut self, id: StateId) -> Result<(), Error> where Self: ExpandedFst<W> {
+        self.check_state(id)?;
+        self.set_start(id);
+        Ok(())
+    }
+    fn try_del_state(&mut self, id: StateId) -> Result<(), Error> where Self: ExpandedFst<W> {
+        self.check_state(id)?;
+        self.del_state(id);
+        Ok(())
+    }
+    fn try_del_states<T: IntoIterator<Item=StateId>>(&mut self, states: T) -> Result<(), Error> where Self: ExpandedFst<W> {
+        let states = states.into_iter().collect::<Vec<_>>();
+        for id in &states {
+            self.check_state(*id)?;
+        }
+        self.del_states(states);
+        Ok(())
+    }
+    fn try_add_arc(&mut self, source: StateId, target: StateId, ilabel: Label, olabel: Label, weight: W) -> Result<(), Error> where Self: ExpandedFst<W> {
+        self.check_state(source)?;
+        self.check_state(target)?;
+        self.add_arc(source, target, ilabel, olabel, weight);
+        Ok(())
+    }
+    fn try_set_arc(&mut self, source: StateId, index: usize, target: StateId, ilabel: Label, olabel: Label, weight: W) -> Result<(), Error> where Self: ExpandedFst<W> {
+        self.check_arc(source, index)?;
+        self.check_state(target)?;
+        self.set_arc(source, index, target, ilabel, olabel, weight);
+        Ok(())
+    }
+    fn try_del_arc(&mut self, source: StateId, index: usize) -> Result<(), Error> where Self: ExpandedFst<W> {
+        self.check_arc(source, index)?;
+        self.del_arc(source, index);
+        Ok(())
+    }
+    fn try_del_arcs(&mut self, source: StateId) -> Result<(), Error> where Self: ExpandedFst<W> {
+        self.check_state(source)?;
+        self.del_arcs(source);
+        Ok(())
+    }
+    fn try_set_finalweight(&mut self, id: StateId, finalweight: W) -> Result<(), Error> where Self: ExpandedFst<W> {
+        self.check_state(id)?;
+        self.set_finalweight(id, finalweight);
+        Ok(())
+    }
 }
 
 pub trait ExpandedFst<W: Weight>: Fst<W> + Clone {
     fn get_numstates(&self) -> usize;
+    fn num_arcs(&self, id: StateId) -> usize;
+    fn num_input_epsilons(&self, id: StateId) -> usize;
+    fn num_output_epsilons(&self, id: StateId) -> usize;
+    fn total_arcs(&self) -> usize {
+        (0..self.get_numstates()).map(|i| self.num_arcs(i)).sum()
+    }
+    /// Returns the known properties in `mask`. If `test` is set, any
+    /// of these that are unknown are first computed.
+    fn properties(&self, mask: Properties, test: bool) -> Properties;
+    /// Returns an error if `id` is not a state of the Fst
+    fn check_state(&self, id: StateId) -> Result<(), Error> {
+        if id < self.get_numstates() { Ok(()) } else { Err(Error::InvalidState(id)) }
+    }
+    /// Returns an error if `index` is not an arc of state `id`
+    fn check_arc(&self, id: StateId, index: usize) -> Result<(), Error> {
+        self.check_state(id)?;
+        if index < self.num_arcs(id) { Ok(()) } else { Err(Error::InvalidArc(id, index)) }
+    }
 }
 
 pub trait Arc<W: Weight>: PartialEq + Debug + Clone  {
@@ -79,6 +170,24 @@ pub trait Arc<W: Weight>: PartialEq + Debug + Clone  {
     fn nextstate(&self) -> StateId;
 }
 
+/// An arc obtained through `MutableFst::arc_iter_mut()` which allows
+/// its fields to be changed in place.
+pub trait MutableArc<W: Weight>: Arc<W> {
+    fn set_ilabel(&mut self, ilabel: Label);
+    fn set_olabel(&mut self, olabel: Label);
+    fn set_weight(&mut self, weight: W);
+    fn set_nextstate(&mut self, nextstate: StateId);
+    /// Fallible variant of `set_nextstate()`, returning an error for an
+    /// invalid state id instead of panicking
+    fn try_set_nextstate(&mut self, nextstate: StateId) -> Result<(), Error>;
+}
+
+////////////////////////////////////////////////////////////////////////////////
+////////// FST PROPERTIES
+////////////////////////////////////////////////////////////////////////////////
+pub mod properties;
+use properties::Properties;
+
 ////////////////////////////////////////////////////////////////////////////////
 ////////// GENERIC FST ALGORITHMS
 ////////////////////////////////////////////////////////////////////////////////
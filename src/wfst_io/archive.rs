@@ -0,0 +1,230 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements archives of multiple Fsts in a single file,
+//! each identified by a (unique) string key, e.g. one lattice per
+//! utterance.
+//!
+//! An archive consists of:
+//!
+//!  * `ARCHIVE_MAGIC` and the format version (`u32`),
+//!  * a sequence of records, each a key and a native Fst file (see
+//!    `serialise()`),
+//!  * an index of keys and record offsets, for random access,
+//!  * and a footer with the offset of the index and `ARCHIVE_MAGIC`.
+//!
+//! Records, index and version are encoded with bincode, the footer
+//! offset as little-endian `u64`. Since each record is a complete
+//! native file, Fsts of different types can be mixed.
+
+extern crate serde;
+use self::serde::Serialize;
+use self::serde::de::DeserializeOwned;
+extern crate bincode;
+use self::bincode::Options;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write, Seek, SeekFrom, BufReader, BufWriter};
+
+use super::{IOWrapper, serialise, deserialise_wrapper, deserialise_data, bincode_options};
+use super::super::{Fst, Error};
+use super::super::semiring::Weight;
+
+/// Identifies archive files ("WFAR" in ASCII)
+pub const ARCHIVE_MAGIC: [u8; 4] = *b"WFAR";
+/// Current version of the archive format
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const FOOTER_LEN: i64 = 12;
+
+/// Whether `d` starts like an archive (rather than a single Fst)
+pub fn is_archive(d: &[u8]) -> bool {
+    d.starts_with(&ARCHIVE_MAGIC)
+}
+
+/// Writes an archive record by record, with the index written by
+/// `finish()`.
+pub struct ArchiveWriter<O: Write> {
+    writer: O,
+    pos: u64,
+    index: Vec<(String, u64)>,
+    keys: HashMap<String, usize>,
+}
+
+impl<O: Write> ArchiveWriter<O> {
+    pub fn new(mut writer: O) -> Result<Self, Error> {
+        writer.write_all(&ARCHIVE_MAGIC)?;
+        let version = bincode::serialize(&ARCHIVE_VERSION)?;
+        writer.write_all(&version)?;
+        Ok(ArchiveWriter {writer,
+                          pos: (ARCHIVE_MAGIC.len() + version.len()) as u64,
+                          index: Vec::new(),
+                          keys: HashMap::new()})
+    }
+
+    /// Adds `fst` with `key`, which must not already be in the archive.
+    pub fn add<W: Weight, F: Fst<W> + Serialize>(&mut self, key: &str, fst: &F) -> Result<(), Error> {
+        if self.keys.contains_key(key) {
+            return Err(Error::DuplicateKey(String::from(key)))
+        }
+        let record = bincode::serialize(&(key, serialise(fst)?))?;
+        self.writer.write_all(&record)?;
+        self.keys.insert(String::from(key), self.index.len());
+        self.index.push((String::from(key), self.pos));
+        self.pos += record.len() as u64;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Writes the index and footer, returning the underlying writer.
+    pub fn finish(mut self) -> Result<O, Error> {
+        self.writer.write_all(&bincode::serialize(&self.index)?)?;
+        self.writer.write_all(&self.pos.to_le_bytes())?;
+        self.writer.write_all(&ARCHIVE_MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl ArchiveWriter<BufWriter<File>> {
+    pub fn create(filename: &str) -> Result<Self, Error> {
+        Self::new(BufWriter::new(File::create(filename)?))
+    }
+}
+
+/// Reads records of an archive by key or in order.
+pub struct ArchiveReader<R: Read + Seek> {
+    reader: R,
+    // Length of the archive, bounding the length of records
+    len: u64,
+    version: u32,
+    index: Vec<(String, u64)>,
+    keys: HashMap<String, usize>,
+}
+
+impl<R: Read + Seek> ArchiveReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != ARCHIVE_MAGIC {
+            return Err(Error::format("not an archive"))
+        }
+        let version: u32 = bincode_options(4).deserialize_from(&mut reader)?;
+        if version > ARCHIVE_VERSION {
+            return Err(Error::Unsupported(format!("archive version {}", version)))
+        }
+
+        let len = reader.seek(SeekFrom::End(0))?;
+        if len < (ARCHIVE_MAGIC.len() + 4) as u64 + FOOTER_LEN as u64 {
+            return Err(Error::format("archive footer not found (incomplete archive?)"))
+        }
+        let mut footer = [0u8; FOOTER_LEN as usize];
+        reader.seek(SeekFrom::End(-FOOTER_LEN))?;
+        reader.read_exact(&mut footer)?;
+        if footer[8..] != ARCHIVE_MAGIC {
+            return Err(Error::format("archive footer not found (incomplete archive?)"))
+        }
+        let mut offset = [0u8; 8];
+        offset.copy_from_slice(&footer[..8]);
+        let offset = u64::from_le_bytes(offset);
+        if offset > len - FOOTER_LEN as u64 {
+            return Err(Error::format("archive index offset out of range"))
+        }
+        reader.seek(SeekFrom::Start(offset))?;
+        let index: Vec<(String, u64)> = bincode_options(len - offset).deserialize_from(&mut reader)?;
+        if index.iter().any(|(_, pos)| *pos >= offset) {
+            return Err(Error::format("archive record offset out of range"))
+        }
+        let keys = index.iter().enumerate().map(|(i, (key, _))| (key.clone(), i)).collect();
+        Ok(ArchiveReader {reader, len, version, index, keys})
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Iterates over the keys in archive order
+    pub fn keys(&self) -> impl Iterator<Item=&str> {
+        self.index.iter().map(|(key, _)| key.as_str())
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.keys.contains_key(key)
+    }
+
+    fn read_record(&mut self, i: usize) -> Result<(String, IOWrapper), Error> {
+        let pos = self.index[i].1;
+        self.reader.seek(SeekFrom::Start(pos))?;
+        let (key, data): (String, Vec<u8>) = bincode_options(self.len - pos).deserialize_from(&mut self.reader)?;
+        Ok((key, deserialise_wrapper(&data)?))
+    }
+
+    /// The (still serialised) Fst with `key`, e.g. to be used with
+    /// `wfstio_wrapper_apply!` if the type is not known
+    pub fn get_wrapper(&mut self, key: &str) -> Result<IOWrapper, Error> {
+        match self.keys.get(key) {
+            Some(&i) => self.read_record(i).map(|(_, wrapped)| wrapped),
+            None => Err(Error::KeyNotFound(String::from(key))),
+        }
+    }
+
+    /// The Fst with `key`, which must be of type `F`
+    pub fn get<W: Weight, F: Fst<W> + DeserializeOwned>(&mut self, key: &str) -> Result<F, Error> {
+        let wrapped = self.get_wrapper(key)?;
+        if wrapped.is::<W, F>() {
+            deserialise_data(&wrapped.data)
+        } else {
+            Err(Error::UnsupportedType{fsttype: wrapped.fsttype, wtype: wrapped.wtype}.in_entry(key))
+        }
+    }
+
+    /// Iterates over `(key, IOWrapper)` records in archive order
+    pub fn iter(&mut self) -> ArchiveIter<'_, R> {
+        ArchiveIter {archive: self, next: 0}
+    }
+}
+
+impl ArchiveReader<BufReader<File>> {
+    pub fn open(filename: &str) -> Result<Self, Error> {
+        Self::new(BufReader::new(File::open(filename)?))
+    }
+}
+
+pub struct ArchiveIter<'a, R: Read + Seek + 'a> {
+    archive: &'a mut ArchiveReader<R>,
+    next: usize,
+}
+
+impl<'a, R: Read + Seek + 'a> Iterator for ArchiveIter<'a, R> {
+    type Item = Result<(String, IOWrapper), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next < self.archive.len() {
+            self.next += 1;
+            Some(self.archive.read_record(self.next - 1))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,240 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements reading of back-off n-gram language models
+//! in the ARPA text format as acceptors over words, in the same way as
+//! Kaldi's `arpa2fst` and the OpenGrm NGram library.
+//!
+//! There is a state for each history (n-gram of less than the highest
+//! order not ending in `</s>`), with the empty history (unigram state)
+//! as the back-off root. Each n-gram `h w` with log10 probability `p`
+//! becomes an arc from the state of `h` labelled `w` with cost `-p *
+//! ln(10)` to the state of the longest suffix of `h w` that is a
+//! history. Each history state except the root has a back-off arc to
+//! the state of its history without the first word, with its back-off
+//! weight as cost.
+//!
+//! `<s>` is not a label: the start state is the state of history
+//! `<s>`. `</s>` is not a label either: its probabilities become
+//! final weights.
+
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::rc::Rc;
+
+use super::super::Error;
+use super::super::semiring::Weight;
+use super::super::semiring::float::Float;
+use super::super::semiring::floatweight::TropicalWeight;
+use super::super::symboltable::SymbolTable;
+use super::super::{MutableFst, StateId, Label};
+
+/// The sentence start word
+pub const BOS: &str = "<s>";
+/// The sentence end word
+pub const EOS: &str = "</s>";
+/// The symbol added for failure back-off arcs
+pub const PHI_SYMBOL: &str = "#phi";
+
+/// Label used on back-off arcs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArpaBackoff {
+    /// Epsilon (label 0): the result is an approximation of the model,
+    /// since lower order n-grams can be reached when a higher order one
+    /// exists
+    Epsilon,
+    /// A failure (phi) label `PHI_SYMBOL`, to be taken only when no
+    /// other arc matches
+    Failure,
+}
+
+struct NGram {
+    lineno: usize,
+    words: Vec<Label>,
+    prob: f64,
+    backoff: f64,
+}
+
+fn line_error(lineno: usize, what: &str) -> Error {
+    Error::format_at(lineno, &format!("ARPA: {}", what))
+}
+
+fn parse_number(s: &str, lineno: usize) -> Result<f64, Error> {
+    s.parse().map_err(|_| line_error(lineno, &format!("bad number '{}'", s)))
+}
+
+/// Reads an ARPA language model from `reader`. Words are mapped to labels
+/// using `words` (which must contain all words, and `PHI_SYMBOL` for
+/// `ArpaBackoff::Failure`) if given, otherwise a symbol table is built
+/// from the unigrams (with `<eps>` as label 0). The symbol table is set
+/// on the Fst.
+pub fn read_arpa<T, F, R>(reader: R, words: Option<Rc<SymbolTable>>, backoff: ArpaBackoff) -> Result<F, Error>
+    where T: Float<T>,
+          F: MutableFst<TropicalWeight<T>>,
+          R: BufRead,
+{
+    let mut symtab = match words {
+        Some(ref words) => (**words).clone(),
+        None => {
+            let mut symtab = SymbolTable::new("");
+            symtab.add_symbol("<eps>");
+            symtab
+        },
+    };
+    let backofflabel = match backoff {
+        ArpaBackoff::Epsilon => 0,
+        ArpaBackoff::Failure => match (words.is_some(), symtab.find_label(PHI_SYMBOL)) {
+            (_, Some(label)) => label,
+            (false, None) => symtab.add_symbol(PHI_SYMBOL),
+            (true, None) => return Err(Error::format(&format!("ARPA: '{}' not in symbol table", PHI_SYMBOL))),
+        },
+    };
+
+    // Read the counts and the n-grams of each order
+    let mut counts: Vec<usize> = Vec::new();
+    let mut ngrams: Vec<Vec<NGram>> = Vec::new();
+    let mut section = None;
+    let mut seen_data = false;
+    let mut seen_end = false;
+    for (i, line) in reader.lines().enumerate() {
+        let lineno = i + 1;
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || seen_end {
+            continue
+        }
+        if line == "\\data\\" {
+            seen_data = true;
+            section = Some(0);
+        } else if line == "\\end\\" {
+            seen_end = true;
+        } else if line.starts_with('\\') && line.ends_with("-grams:") {
+            let order: usize = line[1..line.len() - "-grams:".len()].parse()
+                .map_err(|_| line_error(lineno, &format!("bad section header '{}'", line)))?;
+            if order != ngrams.len() + 1 || order > counts.len() {
+                return Err(line_error(lineno, &format!("unexpected section header '{}'", line)))
+            }
+            // Counts are checked after reading rather than trusted here
+            ngrams.push(Vec::new());
+            section = Some(order);
+        } else {
+            match section {
+                // Text before `\data\` is ignored
+                None => (),
+                Some(0) => {
+                    let count = line.strip_prefix("ngram ")
+                        .and_then(|s| {
+                            let mut kv = s.splitn(2, '=');
+                            match (kv.next().map(|k| k.trim().parse::<usize>()), kv.next().map(|v| v.trim().parse::<usize>())) {
+                                (Some(Ok(order)), Some(Ok(count))) if order == counts.len() + 1 => Some(count),
+                                _ => None,
+                            }
+                        })
+                        .ok_or_else(|| line_error(lineno, &format!("bad count line '{}'", line)))?;
+                    counts.push(count);
+                },
+                Some(order) => {
+                    let fields = line.split_whitespace().collect::<Vec<_>>();
+                    if fields.len() != order + 1 && fields.len() != order + 2 {
+                        return Err(line_error(lineno, &format!("wrong number of fields ({}) for {}-gram", fields.len(), order)))
+                    }
+                    let prob = parse_number(fields[0], lineno)?;
+                    let backoff = if fields.len() == order + 2 { parse_number(fields[order + 1], lineno)? } else { 0.0 };
+                    let mut labels = Vec::with_capacity(order);
+                    for word in &fields[1..order + 1] {
+                        let label = match (words.is_some(), symtab.find_label(word)) {
+                            (_, Some(label)) => label,
+                            (false, None) if order == 1 => symtab.add_symbol(word),
+                            (false, None) => return Err(line_error(lineno, &format!("word '{}' not in unigrams", word))),
+                            (true, None) => return Err(line_error(lineno, &format!("word '{}' not in symbol table", word))),
+                        };
+                        labels.push(label);
+                    }
+                    ngrams[order - 1].push(NGram{lineno, words: labels, prob, backoff});
+                },
+            }
+        }
+    }
+    if !seen_data {
+        return Err(Error::format("ARPA: missing \\data\\ section"))
+    }
+    if !seen_end {
+        return Err(Error::format("ARPA: missing \\end\\"))
+    }
+    if counts.is_empty() {
+        return Err(Error::format("ARPA: no n-grams"))
+    }
+    if ngrams.len() != counts.len() {
+        return Err(Error::format(&format!("ARPA: missing {}-grams section", ngrams.len() + 1)))
+    }
+    for (i, (count, grams)) in counts.iter().zip(ngrams.iter()).enumerate() {
+        if *count != grams.len() {
+            return Err(Error::format(&format!("ARPA: expected {} {}-grams, found {}", count, i + 1, grams.len())))
+        }
+    }
+    let bos = symtab.find_label(BOS);
+    let eos = symtab.find_label(EOS);
+    let maxorder = counts.len();
+
+    // Subtracting from zero avoids negative zero costs
+    let cost = |logprob: f64| -> TropicalWeight<T> {
+        TropicalWeight::new(Some(T::from_f64(0.0 - logprob * ::std::f64::consts::LN_10)))
+    };
+
+    // Create the history states, the root (empty history) is state 0
+    let mut fst = F::new();
+    let mut histories: HashMap<Vec<Label>, StateId> = HashMap::new();
+    histories.insert(Vec::new(), fst.add_state(TropicalWeight::zero()));
+    for grams in ngrams.iter().take(maxorder - 1) {
+        for ngram in grams {
+            if Some(*ngram.words.last().unwrap()) != eos {
+                histories.insert(ngram.words.clone(), fst.add_state(TropicalWeight::zero()));
+            }
+        }
+    }
+
+    // The state of the longest suffix of `words` that is a history
+    let suffix_state = |words: &[Label]| -> StateId {
+        (0..words.len()).filter_map(|i| histories.get(&words[i..])).next().cloned().unwrap_or(0)
+    };
+
+    // Back-off arcs
+    for grams in ngrams.iter().take(maxorder - 1) {
+        for ngram in grams {
+            if let Some(&state) = histories.get(&ngram.words) {
+                let lower = suffix_state(&ngram.words[1..]);
+                fst.add_arc(state, lower, backofflabel, backofflabel, cost(ngram.backoff));
+            }
+        }
+    }
+
+    // N-gram arcs and final weights
+    for grams in &ngrams {
+        for ngram in grams {
+            let (history, word) = ngram.words.split_at(ngram.words.len() - 1);
+            let word = word[0];
+            let source = match histories.get(history) {
+                Some(&state) => state,
+                None => return Err(line_error(ngram.lineno, "n-gram history is not an n-gram")),
+            };
+            if Some(word) == eos {
+                fst.set_finalweight(source, cost(ngram.prob));
+            } else if Some(word) != bos {
+                let target = suffix_state(&ngram.words);
+                fst.add_arc(source, target, word, word, cost(ngram.prob));
+            }
+        }
+    }
+
+    let start = bos.and_then(|bos| histories.get(&vec![bos]).cloned()).unwrap_or(0);
+    fst.set_start(start);
+    let symtab = Rc::new(symtab);
+    fst.set_isyms(symtab.clone());
+    fst.set_osyms(symtab);
+    Ok(fst)
+}
@@ -0,0 +1,170 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements transparent compression of streams, so that
+//! compressed files (of any format) can be read and written through the
+//! usual `Read` and `Write` interfaces.
+//!
+//! Compressed input is detected by its magic number (gzip, and zstd if
+//! built with the optional `zstd` feature).
+
+extern crate flate2;
+#[cfg(feature = "zstd")]
+extern crate zstd;
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Chain, Cursor, Read, Write};
+use std::str::FromStr;
+
+use self::flate2::bufread::MultiGzDecoder;
+use self::flate2::write::GzEncoder;
+
+use super::super::Error;
+
+/// Identifies gzip streams
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// Identifies zstd streams
+pub const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// The compression of a stream starting with `d`
+    pub fn detect(d: &[u8]) -> Compression {
+        if d.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else if d.starts_with(&ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Compression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Compression::None),
+            "gzip" | "gz" => Ok(Compression::Gzip),
+            "zstd" | "zst" => Ok(Compression::Zstd),
+            _ => Err(Error::Invalid(format!("unknown compression '{}' (expected none, gzip or zstd)", s))),
+        }
+    }
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_unsupported() -> Error {
+    Error::Unsupported(String::from("zstd compression (built without the `zstd` feature)"))
+}
+
+/// Reads the first `n` bytes of `reader` (fewer only at the end of the
+/// input) and returns a reader starting with them again, for which
+/// `fill_buf()` returns all of them. A single `fill_buf()` on `reader`
+/// itself may return fewer bytes (e.g. from a pipe).
+pub fn peek<R: BufRead>(mut reader: R, n: usize) -> io::Result<Chain<Cursor<Vec<u8>>, R>> {
+    let mut start = Vec::with_capacity(n);
+    (&mut reader).take(n as u64).read_to_end(&mut start)?;
+    Ok(Cursor::new(start).chain(reader))
+}
+
+/// Wraps `reader` in a decompressor if its contents are compressed
+/// (peeking at the start of the stream), otherwise returns it as is.
+pub fn decompress<'a, R: BufRead + 'a>(reader: R) -> Result<Box<dyn BufRead + 'a>, Error> {
+    let mut reader = peek(reader, ZSTD_MAGIC.len())?;
+    let compression = Compression::detect(reader.fill_buf()?);
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        Compression::Gzip => Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader)))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(BufReader::new(zstd::stream::read::Decoder::with_buffer(reader)?))),
+        #[cfg(not(feature = "zstd"))]
+        Compression::Zstd => Err(zstd_unsupported()),
+    }
+}
+
+/// A writer compressing its output, `finish()` must be called to
+/// complete the compressed stream
+pub enum CompressedWriter<O: Write> {
+    Plain(O),
+    Gzip(GzEncoder<O>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, O>),
+}
+
+impl<O: Write> CompressedWriter<O> {
+    pub fn new(writer: O, compression: Compression) -> Result<Self, Error> {
+        match compression {
+            Compression::None => Ok(CompressedWriter::Plain(writer)),
+            Compression::Gzip => Ok(CompressedWriter::Gzip(GzEncoder::new(writer, flate2::Compression::default()))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Ok(CompressedWriter::Zstd(zstd::stream::write::Encoder::new(writer, 0)?)),
+            #[cfg(not(feature = "zstd"))]
+            Compression::Zstd => Err(zstd_unsupported()),
+        }
+    }
+
+    /// Completes the compressed stream and returns the underlying writer
+    pub fn finish(self) -> Result<O, Error> {
+        let mut writer = match self {
+            CompressedWriter::Plain(writer) => writer,
+            CompressedWriter::Gzip(encoder) => encoder.finish()?,
+            #[cfg(feature = "zstd")]
+            CompressedWriter::Zstd(encoder) => encoder.finish()?,
+        };
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+impl<O: Write> Write for CompressedWriter<O> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            CompressedWriter::Plain(ref mut writer) => writer.write(buf),
+            CompressedWriter::Gzip(ref mut encoder) => encoder.write(buf),
+            #[cfg(feature = "zstd")]
+            CompressedWriter::Zstd(ref mut encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            CompressedWriter::Plain(ref mut writer) => writer.flush(),
+            CompressedWriter::Gzip(ref mut encoder) => encoder.flush(),
+            #[cfg(feature = "zstd")]
+            CompressedWriter::Zstd(ref mut encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Reads all of `reader`, decompressing if necessary
+pub fn read_all<R: BufRead>(reader: R) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+    decompress(reader)?.read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
@@ -0,0 +1,141 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+// This file contains portions of code ported from OpenFst
+// (http://www.openfst.org) under the following licence and
+// attribution:
+//
+// """
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Copyright 2005-2010 Google, Inc.
+// Author: allauzen@google.com (Cyril Allauzen)
+// """
+////////////////////////////////////////////////////////////////////////////////
+
+//! This module implements writing of Fsts in the GraphViz DOT format
+//! (as OpenFst's `fstdraw`), e.g. to be rendered with `dot -Tpdf`.
+//!
+//! The start state is drawn in bold and final states as double
+//! circles. Weights equal to `W::one()` are not shown and labels are
+//! written as symbols if the Fst has symbol tables.
+
+use std::fmt::Display;
+use std::io::Write;
+use std::rc::Rc;
+
+use super::super::Error;
+use super::super::semiring::Weight;
+use super::super::symboltable::SymbolTable;
+use super::super::{ExpandedFst, Label, Arc};
+
+/// Options for `write_dot()`
+#[derive(Clone, Debug)]
+pub struct DotOptions {
+    /// Title of the drawing
+    pub title: String,
+    /// Draw a single label per arc
+    pub acceptor: bool,
+    /// Portrait rather than landscape orientation
+    pub portrait: bool,
+    /// Drawing size in inches
+    pub width: f32,
+    pub height: f32,
+    pub fontsize: u32,
+    /// Truncate labels (symbols or numbers) longer than this
+    pub max_label_len: Option<usize>,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        DotOptions {title: String::new(),
+                    acceptor: false,
+                    portrait: false,
+                    width: 8.5,
+                    height: 11.0,
+                    fontsize: 14,
+                    max_label_len: None}
+    }
+}
+
+// Quotes characters which are special in DOT strings
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn label(syms: &Option<Rc<SymbolTable>>, l: Label, options: &DotOptions) -> String {
+    let s = match *syms {
+        Some(ref syms) => String::from(syms.find_symbol(l).unwrap_or("_")),
+        None => l.to_string(),
+    };
+    match options.max_label_len {
+        Some(n) if s.chars().count() > n => format!("{}...", s.chars().take(n).collect::<String>()),
+        _ => s,
+    }
+}
+
+/// Writes `fst` in DOT format to `writer`.
+pub fn write_dot<W, F, O>(fst: &F, writer: &mut O, options: &DotOptions) -> Result<(), Error>
+    where W: Weight + Display,
+          F: ExpandedFst<W>,
+          O: Write,
+{
+    let isyms = fst.get_isyms();
+    let osyms = fst.get_osyms();
+
+    writeln!(writer, "digraph FST {{")?;
+    writeln!(writer, "rankdir = LR;")?;
+    writeln!(writer, "size = \"{},{}\";", options.width, options.height)?;
+    writeln!(writer, "label = \"{}\";", escape(&options.title))?;
+    writeln!(writer, "center = 1;")?;
+    writeln!(writer, "orientation = {};", if options.portrait { "Portrait" } else { "Landscape" })?;
+    writeln!(writer, "ranksep = \"0.4\";")?;
+    writeln!(writer, "nodesep = \"0.25\";")?;
+
+    for i in 0..fst.get_numstates() {
+        let mut statelabel = i.to_string();
+        let shape = if fst.is_final(i) {
+            let weight = fst.get_finalweight(i);
+            if weight != W::one() {
+                statelabel.push_str(&format!("/{}", weight));
+            }
+            "doublecircle"
+        } else {
+            "circle"
+        };
+        let style = if fst.get_start() == Some(i) { "bold" } else { "solid" };
+        writeln!(writer, "{} [label = \"{}\", shape = {}, style = {}, fontsize = {}]",
+                 i, escape(&statelabel), shape, style, options.fontsize)?;
+
+        for arc in fst.arc_iter(i) {
+            let mut arclabel = label(&isyms, arc.ilabel(), options);
+            if !options.acceptor {
+                arclabel.push(':');
+                arclabel.push_str(&label(&osyms, arc.olabel(), options));
+            }
+            let weight = arc.weight();
+            if weight != W::one() {
+                arclabel.push_str(&format!("/{}", weight));
+            }
+            writeln!(writer, "\t{} -> {} [label = \"{}\", fontsize = {}];",
+                     i, arc.nextstate(), escape(&arclabel), options.fontsize)?;
+        }
+    }
+    writeln!(writer, "}}")?;
+    Ok(())
+}
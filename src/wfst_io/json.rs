@@ -0,0 +1,154 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements reading and writing of Fsts in a
+//! human-readable JSON format, for inspection and generation by other
+//! tools. For example (an Fst with `TropicalWeight<f32>` weights):
+//!
+//! ```text
+//! {
+//!   "wtype": "tropical32",
+//!   "start": 0,
+//!   "states": [
+//!     {"arcs": [{"ilabel": 1, "olabel": 2, "weight": "0.5", "nextstate": 1}]},
+//!     {"final": "1.5", "arcs": []}
+//!   ],
+//!   "isyms": {"name": "in", "symbols": [[0, "<eps>"], [1, "a"]]},
+//!   "osyms": null
+//! }
+//! ```
+//!
+//!  * `wtype`: the Weight type (see `Weight::wtype()`), which must
+//!    match when reading.
+//!  * `start`: the start state or `null`.
+//!  * `states`: indexed by state id, each with a list of `arcs` and a
+//!    `final` weight for final states (omitted otherwise).
+//!  * Weights are strings as written by `Display` and read by
+//!    `Weight::parse_weight()`, since not all weights are numbers (and
+//!    e.g. `inf` is not a valid JSON number). An omitted arc `weight`
+//!    is `W::one()`.
+//!  * `isyms`/`osyms`: symbol tables with a `name` and a list of
+//!    `[label, symbol]` pairs, or `null`.
+
+extern crate serde;
+use self::serde::{Serialize, Deserialize};
+extern crate serde_json;
+
+use std::fmt::Display;
+use std::io::{Read, Write};
+use std::rc::Rc;
+
+use super::super::Error;
+use super::super::semiring::Weight;
+use super::super::symboltable::SymbolTable;
+use super::super::{ExpandedFst, MutableFst, StateId, Label, Arc};
+
+#[derive(Serialize, Deserialize)]
+struct JsonFst {
+    wtype: String,
+    start: Option<StateId>,
+    states: Vec<JsonState>,
+    #[serde(default)]
+    isyms: Option<SymbolTable>,
+    #[serde(default)]
+    osyms: Option<SymbolTable>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonState {
+    #[serde(rename = "final", default, skip_serializing_if = "Option::is_none")]
+    finalweight: Option<String>,
+    #[serde(default)]
+    arcs: Vec<JsonArc>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonArc {
+    ilabel: Label,
+    olabel: Label,
+    #[serde(default)]
+    weight: Option<String>,
+    nextstate: StateId,
+}
+
+fn parse_weight<W: Weight>(s: &str) -> Result<W, Error> {
+    W::parse_weight(s).map_err(|e| Error::format(&e.to_string()))
+}
+
+/// Writes `fst` as (pretty-printed) JSON to `writer`.
+pub fn write_json<W, F, O>(fst: &F, writer: &mut O) -> Result<(), Error>
+    where W: Weight + Display,
+          F: ExpandedFst<W>,
+          O: Write,
+{
+    let states = (0..fst.get_numstates()).map(|i| {
+        JsonState {finalweight: if fst.is_final(i) { Some(fst.get_finalweight(i).to_string()) } else { None },
+                   arcs: fst.arc_iter(i).map(|arc| {
+                       JsonArc {ilabel: arc.ilabel(),
+                                olabel: arc.olabel(),
+                                weight: Some(arc.weight().to_string()),
+                                nextstate: arc.nextstate()}
+                   }).collect()}
+    }).collect();
+    let json = JsonFst {wtype: W::wtype(),
+                        start: fst.get_start(),
+                        states,
+                        isyms: fst.get_isyms().map(|syms| (*syms).clone()),
+                        osyms: fst.get_osyms().map(|syms| (*syms).clone())};
+    serde_json::to_writer_pretty(&mut *writer, &json)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Reads an Fst in JSON format from `reader`. The weight type must
+/// match `W`.
+pub fn read_json<W, F, R>(reader: R) -> Result<F, Error>
+    where W: Weight,
+          F: MutableFst<W>,
+          R: Read,
+{
+    let json: JsonFst = serde_json::from_reader(reader)?;
+    if json.wtype != W::wtype() {
+        return Err(Error::UnsupportedWeight(json.wtype))
+    }
+    let nstates = json.states.len();
+    let mut fst = F::new();
+    if let Some(isyms) = json.isyms {
+        fst.set_isyms(Rc::new(isyms));
+    }
+    if let Some(osyms) = json.osyms {
+        fst.set_osyms(Rc::new(osyms));
+    }
+    fst.reserve_states(nstates);
+    for state in &json.states {
+        fst.add_state(match state.finalweight {
+            Some(ref w) => parse_weight(w)?,
+            None => W::zero(),
+        });
+    }
+    if let Some(start) = json.start {
+        if start >= nstates {
+            return Err(Error::format(&format!("start state {} out of range", start)))
+        }
+        fst.set_start(start);
+    }
+    for (i, state) in json.states.into_iter().enumerate() {
+        fst.reserve_arcs(i, state.arcs.len());
+        for arc in state.arcs {
+            if arc.nextstate >= nstates {
+                return Err(Error::format(&format!("state {} out of range (arc from state {})", arc.nextstate, i)))
+            }
+            let weight = match arc.weight {
+                Some(ref w) => parse_weight(w)?,
+                None => W::one(),
+            };
+            fst.add_arc(i, arc.nextstate, arc.ilabel, arc.olabel, weight);
+        }
+    }
+    Ok(fst)
+}
@@ -0,0 +1,138 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements reading and writing of lattices in the text
+//! archive formats of the Kaldi speech recognition toolkit (as read
+//! and written with e.g. `lattice-copy ark:- ark,t:-`).
+//!
+//! Each lattice is preceded by its key (utterance id) on its own line
+//! and followed by an empty line (see `text::split_text_archive()`).
+//! Weights are `LatticeWeight`s written as `graph,acoustic`:
+//!
+//!  * `Lattice`: transducers from transition ids to words, in AT&T
+//!    format (`src tgt tid word [weight]`).
+//!  * `Compact`: acceptors over words, with the transition ids of each
+//!    arc appended to its weight (`src tgt word [graph,acoustic,tids]`
+//!    where `tids` are separated by `_`).
+//!
+//! Compact lattices are read and written without their alignments: the
+//! transition ids are discarded when reading (there is no place for
+//! them in a `LatticeWeight`) and written as empty strings, so reading
+//! and writing a compact lattice loses its alignment. The output is
+//! still a valid compact lattice, but not for Kaldi tools that need the
+//! alignment (e.g. `lattice-align-words`). Use `Lattice` (with
+//! `lattice-copy --write-compact=false`) to keep transition ids, as
+//! input labels.
+//!
+//! Labels are always integers in these formats, a word symbol table
+//! (e.g. Kaldi's `words.txt`) can be attached to the word labels. Use
+//! `algorithms::arcmap::LatticeScaleMapper` to combine the costs into
+//! `TropicalWeight`s.
+
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+
+use super::super::Error;
+use super::text::{read_att, split_text_archive};
+use super::super::semiring::Weight;
+use super::super::semiring::float::Float;
+use super::super::semiring::latticeweight::LatticeWeight;
+use super::super::symboltable::SymbolTable;
+use super::super::{ExpandedFst, MutableFst, Arc};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KaldiLatticeType {
+    Lattice,
+    Compact,
+}
+
+// Removes the transition ids (the alignment) from the weight field of a
+// compact lattice line, leaving `graph,acoustic`
+fn strip_alignment(line: &str) -> String {
+    let mut fields = line.split_whitespace().collect::<Vec<_>>();
+    if fields.len() == 2 || fields.len() == 4 {
+        let n = fields.len();
+        let weight = fields[n - 1];
+        fields[n - 1] = match weight.match_indices(',').nth(1) {
+            Some((i, _)) => &weight[..i],
+            None => weight,
+        };
+    }
+    fields.join("\t")
+}
+
+/// Reads all lattices from a Kaldi text archive, as `(key, lattice)`
+/// pairs. The word labels (output labels for `Lattice`) are given the
+/// symbol table `words`. The transition ids of `Compact` lattices are
+/// discarded, the lattices read are alignment-free.
+pub fn read_kaldi_lattices<T, F, R>(mut reader: R, ltype: KaldiLatticeType, words: Option<Rc<SymbolTable>>) -> Result<Vec<(String, F)>, Error>
+    where T: Float<T>,
+          F: MutableFst<LatticeWeight<T>>,
+          R: BufRead,
+{
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut lattices = Vec::new();
+    for (key, fsttext) in split_text_archive(&text) {
+        let result = match ltype {
+            KaldiLatticeType::Lattice => read_att(fsttext.as_bytes(), None, None, false),
+            KaldiLatticeType::Compact => {
+                let fsttext = fsttext.lines().map(strip_alignment).collect::<Vec<_>>().join("\n");
+                read_att(fsttext.as_bytes(), None, None, true)
+            },
+        };
+        let mut fst: F = result.map_err(|e| e.in_entry(&key))?;
+        if let Some(ref words) = words {
+            if ltype == KaldiLatticeType::Compact {
+                fst.set_isyms(words.clone());
+            }
+            fst.set_osyms(words.clone());
+        }
+        lattices.push((key, fst));
+    }
+    Ok(lattices)
+}
+
+/// Writes `fst` as an entry with `key` of a Kaldi text archive, starting
+/// with the start state. For `Compact` lattices the output (word)
+/// labels are written, with no transition ids (the output is
+/// alignment-free, whether or not the input lattice had one).
+pub fn write_kaldi_lattice<T, F, O>(key: &str, fst: &F, ltype: KaldiLatticeType, writer: &mut O) -> Result<(), Error>
+    where T: Float<T> + Display,
+          F: ExpandedFst<LatticeWeight<T>>,
+          O: Write,
+{
+    // Weights equal to `one()` are omitted
+    let weight = |w: LatticeWeight<T>| {
+        if w == LatticeWeight::one() {
+            String::new()
+        } else if ltype == KaldiLatticeType::Compact {
+            format!("\t{},", w)
+        } else {
+            format!("\t{}", w)
+        }
+    };
+    writeln!(writer, "{} ", key)?;
+    let startstate = fst.get_start();
+    let states = startstate.into_iter().chain((0..fst.get_numstates()).filter(|i| Some(*i) != startstate));
+    for i in states {
+        for arc in fst.arc_iter(i) {
+            match ltype {
+                KaldiLatticeType::Lattice => write!(writer, "{}\t{}\t{}\t{}", i, arc.nextstate(), arc.ilabel(), arc.olabel())?,
+                KaldiLatticeType::Compact => write!(writer, "{}\t{}\t{}", i, arc.nextstate(), arc.olabel())?,
+            }
+            writeln!(writer, "{}", weight(arc.weight()))?;
+        }
+        if fst.is_final(i) {
+            writeln!(writer, "{}{}", i, weight(fst.get_finalweight(i)))?;
+        }
+    }
+    writeln!(writer)?;
+    Ok(())
+}
@@ -0,0 +1,327 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements a wrapper and utilities for simplifying IO
+//! of different supported types.
+//!
+//! Native files consist of a `Header` identifying the format version,
+//! Fst type and Weight type, followed by the serialised Fst. Files
+//! written before format version 1 are tagged with a build-specific
+//! `TypeId` instead, which does not identify the weight type, so they
+//! can only be read as a named type (see `deserialise()`).
+//!
+//! Other file formats are implemented in submodules:
+//!
+//!  * `openfst`: OpenFst binary files
+//!  * `text`: AT&T text format
+//!  * `dot`: GraphViz DOT format (output only)
+//!  * `json`: human-readable JSON format
+//!  * `archive`: multiple keyed Fsts in one file
+//!  * `kaldi`: Kaldi text lattices
+//!  * `slf`: HTK Standard Lattice Format
+//!  * `arpa`: ARPA n-gram language models (input only)
+//!
+//! Native files can be read from and written to streams, compressed
+//! input (gzip or zstd) is detected and decompressed transparently (see
+//! `compression`).
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+
+extern crate serde;
+use self::serde::{Serialize, Deserialize};
+use self::serde::de::DeserializeOwned;
+extern crate bincode;
+use self::bincode::Options;
+
+use super::{Fst, Error};
+use super::semiring::Weight;
+use super::wfst_vec::{VecFst, LegacyVecFst};
+
+pub mod openfst;
+pub mod text;
+pub mod dot;
+pub mod json;
+pub mod archive;
+pub mod kaldi;
+pub mod slf;
+pub mod arpa;
+pub mod compression;
+
+use self::compression::{Compression, CompressedWriter, decompress};
+
+/// Identifies native files ("WFST" in ASCII)
+pub const MAGIC: [u8; 4] = *b"WFST";
+/// Current version of the native file format
+pub const VERSION: u32 = 1;
+
+// Upper bound on the size of a header (the type names are short)
+const HEADER_LIMIT: u64 = 1024;
+
+/// The error type of this module, an alias of `wfst::Error` kept for
+/// compatibility
+pub type IOError = Error;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Header {
+    pub magic: [u8; 4],
+    pub version: u32,
+    pub fsttype: String,
+    pub wtype: String,
+}
+
+/// The header fields of a file and its (still serialised) contents
+#[derive(Debug)]
+pub struct IOWrapper {
+    pub version: u32,
+    pub fsttype: String,
+    pub wtype: String,
+    pub data: Vec<u8>,
+}
+
+impl IOWrapper {
+    /// Whether the contents are of Fst type `F` with Weight type `W`
+    pub fn is<W: Weight, F: Fst<W>>(&self) -> bool {
+        self.fsttype == F::fst_type() && self.wtype == W::wtype()
+    }
+}
+
+// Format before version 1
+#[derive(Debug, Deserialize)]
+struct LegacyIOWrapper {
+    #[allow(dead_code)]
+    tid: String,   //differs between builds, so cannot identify the type
+    data: Vec<u8>,
+}
+
+// Reads the Fst of a legacy file as a `VecFst<W>`, failing unless
+// all of `data` matches the layout
+fn legacy_fst<W: Weight + Serialize + DeserializeOwned>(data: &[u8]) -> Result<VecFst<W>, Error> {
+    let fst: LegacyVecFst<W> = bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .with_limit(data.len() as u64)
+        .deserialize(data)?;
+    Ok(VecFst::from(fst))
+}
+
+// Options matching `bincode::serialize()`, reading at most `limit`
+// bytes: lengths in corrupt input then give errors instead of huge
+// allocations
+fn bincode_options(limit: u64) -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(limit)
+}
+
+/// Deserialises a value read from a file, with lengths bounded by the
+/// size of `d` (used by the macros below)
+#[doc(hidden)]
+pub fn deserialise_data<T: DeserializeOwned>(d: &[u8]) -> Result<T, Error> {
+    Ok(bincode_options(d.len() as u64).deserialize(d)?)
+}
+
+fn wrong_type(fsttype: &str, wtype: &str) -> Error {
+    Error::UnsupportedType{fsttype: String::from(fsttype), wtype: String::from(wtype)}
+}
+
+pub fn serialise<W: Weight, F: Fst<W> + Serialize>(fst: &F) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    serialise_into(fst, &mut buf)?;
+    Ok(buf)
+}
+
+/// Writes `fst` as a native file to `writer`
+pub fn serialise_into<W: Weight, F: Fst<W> + Serialize, O: Write>(fst: &F, mut writer: O) -> Result<(), Error> {
+    let header = Header{magic: MAGIC,
+                        version: VERSION,
+                        fsttype: F::fst_type(),
+                        wtype: W::wtype()};
+    bincode::serialize_into(&mut writer, &header)?;
+    bincode::serialize_into(&mut writer, fst)?;
+    Ok(())
+}
+
+// Reads the rest of the header after `magic`
+fn read_header<R: Read>(magic: [u8; 4], reader: &mut R) -> Result<Header, Error> {
+    let header: Header = bincode_options(HEADER_LIMIT).deserialize_from(&mut (&magic[..]).chain(reader))?;
+    if header.version > VERSION {
+        return Err(Error::Unsupported(format!("native format version {}", header.version)))
+    }
+    Ok(header)
+}
+
+/// Reads the header of a native file, leaving the Fst serialised.
+///
+/// Files written before format version 1 give `Error::LegacyFile`, as
+/// their weight type is not known (the float weights share a layout):
+/// read them with `deserialise()` instead.
+pub fn deserialise_wrapper(d: &[u8]) -> Result<IOWrapper, Error> {
+    if d.starts_with(&MAGIC) {
+        let mut reader = &d[MAGIC.len()..];
+        let header = read_header(MAGIC, &mut reader)?;
+        Ok(IOWrapper{version: header.version,
+                     fsttype: header.fsttype,
+                     wtype: header.wtype,
+                     data: reader.to_vec()})
+    } else {
+        let _: LegacyIOWrapper = deserialise_data(d)?;
+        Err(Error::LegacyFile)
+    }
+}
+
+/// Reads a native file as an Fst of type `F` with Weight type `W`.
+///
+/// Files written before format version 1 hold a `VecFst` and are read
+/// as `VecFst<W>` if they match its layout, as their weight type is not
+/// recorded.
+pub fn deserialise<W: Weight + Serialize + DeserializeOwned, F: Fst<W> + DeserializeOwned>(d: &[u8]) -> Result<F, Error> {
+    if !d.starts_with(&MAGIC) {
+        let wrapped: LegacyIOWrapper = deserialise_data(d)?;
+        if F::fst_type() != VecFst::<W>::fst_type() {
+            return Err(wrong_type(&F::fst_type(), &W::wtype()))
+        }
+        let fst = legacy_fst::<W>(&wrapped.data)
+            .map_err(|_| Error::format(&format!("legacy file does not match the layout of {} weights", W::wtype())))?;
+        return Ok(bincode::deserialize(&bincode::serialize(&fst)?)?)
+    }
+    let wrapped = deserialise_wrapper(d)?;
+
+    if wrapped.is::<W, F>() {
+        deserialise_data(&wrapped.data)
+    } else {
+        Err(wrong_type(&wrapped.fsttype, &wrapped.wtype))
+    }
+}
+
+/// Reads a native file from `reader` (decompressing if necessary),
+/// leaving the Fst serialised
+pub fn deserialise_wrapper_from<R: BufRead>(reader: R) -> Result<IOWrapper, Error> {
+    let mut reader = decompress(reader)?;
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic == MAGIC {
+        let header = read_header(magic, &mut reader)?;
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Ok(IOWrapper{version: header.version,
+                     fsttype: header.fsttype,
+                     wtype: header.wtype,
+                     data})
+    } else {
+        let mut d = magic.to_vec();
+        reader.read_to_end(&mut d)?;
+        deserialise_wrapper(&d)
+    }
+}
+
+/// Reads a native file from `reader` (decompressing if necessary),
+/// checking the header before reading the rest
+pub fn deserialise_from<W: Weight + Serialize + DeserializeOwned, F: Fst<W> + DeserializeOwned, R: BufRead>(reader: R) -> Result<F, Error> {
+    let mut reader = decompress(reader)?;
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic == MAGIC {
+        let header = read_header(magic, &mut reader)?;
+        if header.fsttype == F::fst_type() && header.wtype == W::wtype() {
+            // Read in full, so that lengths are bounded by the input size
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            deserialise_data(&data)
+        } else {
+            Err(wrong_type(&header.fsttype, &header.wtype))
+        }
+    } else {
+        let mut d = magic.to_vec();
+        reader.read_to_end(&mut d)?;
+        deserialise(&d)
+    }
+}
+
+/// Loads a native file, which may be compressed
+pub fn load<W: Weight + Serialize + DeserializeOwned, F: Fst<W> + DeserializeOwned>(filename: &str) -> Result<F, Error> {
+    deserialise_from(BufReader::new(File::open(filename)?))
+}
+
+/// Saves `fst` as a native file with `compression`
+pub fn save<W: Weight, F: Fst<W> + Serialize>(fst: &F, filename: &str, compression: Compression) -> Result<(), Error> {
+    let mut writer = CompressedWriter::new(BufWriter::new(File::create(filename)?), compression)?;
+    serialise_into(fst, &mut writer)?;
+    writer.finish()?;
+    Ok(())
+}
+
+// Tries each of the listed Weight types in turn (used by the macros
+// below)
+#[doc(hidden)]
+#[macro_export]
+macro_rules! wfstio_dispatch {
+    ($w:ident, $fst:ident, $e:expr; ) => {
+        Err(wfst::Error::UnsupportedType{fsttype: $w.fsttype.clone(), wtype: $w.wtype.clone()})
+    };
+    ($w:ident, $fst:ident, $e:expr; $weight:ty $(, $rest:ty)*) => {
+        if $w.is::<$weight, wfst::wfst_vec::VecFst<$weight>>() {
+            match wfst::wfst_io::deserialise_data(&$w.data) {
+                Ok(f) => {
+                    let $fst: wfst::wfst_vec::VecFst<$weight> = f;
+                    $e
+                },
+                Err(e) => Err(e),
+            }
+        } else {
+            wfstio_dispatch!($w, $fst, $e; $($rest),*)
+        }
+    };
+}
+
+/// Applies `$e` to the Fst `$fst` in the `IOWrapper` `$w`, for any of
+/// the supported `VecFst` types
+#[macro_export]
+macro_rules! wfstio_wrapper_apply {
+    ($w:ident, $fst:ident, $e:expr) => { //expression -> Result<(), wfst::Error>
+        wfstio_dispatch!($w, $fst, $e;
+                         wfst::semiring::floatweight::TropicalWeight<f64>,
+                         wfst::semiring::floatweight::LogWeight<f64>,
+                         wfst::semiring::floatweight::MinmaxWeight<f64>,
+                         wfst::semiring::floatweight::RealWeight<f64>,
+                         wfst::semiring::floatweight::ArcticWeight<f64>,
+                         wfst::semiring::floatweight::TropicalWeight<f32>,
+                         wfst::semiring::floatweight::LogWeight<f32>,
+                         wfst::semiring::floatweight::MinmaxWeight<f32>,
+                         wfst::semiring::floatweight::RealWeight<f32>,
+                         wfst::semiring::floatweight::ArcticWeight<f32>,
+                         wfst::semiring::booleanweight::BooleanWeight)
+    }
+}
+
+#[macro_export]
+macro_rules! wfstio_autodeserialise_apply {
+    ($buf:ident, $fst:ident, $e:expr) => { //expression -> Result<(), wfst::Error>
+        match wfst::wfst_io::deserialise_wrapper(&$buf) {
+            Ok(w) => wfstio_wrapper_apply!(w, $fst, $e),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! wfstio_autodeserialise_apply_naturalless {
+    ($buf:ident, $fst:ident, $e:expr) => { //expression -> Result<(), wfst::Error>
+        match wfst::wfst_io::deserialise_wrapper(&$buf) {
+            Ok(w) => wfstio_dispatch!(w, $fst, $e;
+                                      wfst::semiring::floatweight::TropicalWeight<f64>,
+                                      wfst::semiring::floatweight::MinmaxWeight<f64>,
+                                      wfst::semiring::floatweight::ArcticWeight<f64>,
+                                      wfst::semiring::floatweight::TropicalWeight<f32>,
+                                      wfst::semiring::floatweight::MinmaxWeight<f32>,
+                                      wfst::semiring::floatweight::ArcticWeight<f32>,
+                                      wfst::semiring::booleanweight::BooleanWeight),
+            Err(e) => Err(e),
+        }
+    }
+}
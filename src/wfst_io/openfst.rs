@@ -0,0 +1,547 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+// This file contains portions of code ported from OpenFst
+// (http://www.openfst.org) under the following licence and
+// attribution:
+//
+// """
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// Copyright 2005-2010 Google, Inc.
+// Author: riley@google.com (Michael Riley)
+// """
+////////////////////////////////////////////////////////////////////////////////
+
+//! This module implements reading and writing of OpenFst binary files,
+//! so that Fsts can be exchanged with the OpenFst tools without going
+//! through the text format.
+//!
+//! The `vector` and (32-bit) `const` Fst types are supported, including
+//! embedded symbol tables, for the arc types `standard`
+//! (`TropicalWeight<f32>`), `log` (`LogWeight<f32>`), `tropical64` and
+//! `log64`. Values are little-endian, as written by OpenFst on common
+//! platforms. `const` files are written unaligned (the OpenFst default)
+//! but aligned files can be read.
+//!
+//! The property bits stored in a file are trusted when reading, as in
+//! OpenFst.
+
+use std::fs::File;
+use std::io::{Read, Write};
+
+use super::super::Error;
+use super::super::semiring::Weight;
+use super::super::semiring::float::Float;
+use super::super::semiring::floatweight::{TropicalWeight, LogWeight};
+use super::super::properties::{Properties, FST_PROPERTIES};
+use super::super::symboltable::SymbolTable;
+use super::super::{ExpandedFst, MutableFst, Arc};
+
+const FST_MAGIC: i32 = 2125659606;
+const SYMBOLTABLE_MAGIC: i32 = 2125658996;
+
+// Header flags
+const HAS_ISYMBOLS: i32 = 0x1;
+const HAS_OSYMBOLS: i32 = 0x2;
+const IS_ALIGNED: i32 = 0x4;
+
+// OpenFst property bits which have no counterpart in `properties`, the
+// others are ours shifted left by `PROPERTIES_SHIFT`
+const EXPANDED: Properties = 0x1;
+const MUTABLE: Properties = 0x2;
+const PROPERTIES_SHIFT: u32 = 16;
+
+const VECTOR_VERSION: i32 = 2;
+const CONST_VERSION: i32 = 2;
+const CONST_ALIGNED_VERSION: i32 = 1;
+const FILE_ALIGN: usize = 16;
+
+/// The OpenFst Fst types which can be read and written
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpenFstType {
+    Vector,
+    Const,
+}
+
+impl OpenFstType {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            OpenFstType::Vector => "vector",
+            OpenFstType::Const => "const",
+        }
+    }
+}
+
+/// The header at the start of an OpenFst file, which can be used to
+/// determine the arc type before reading the Fst.
+#[derive(Clone, Debug)]
+pub struct OpenFstHeader {
+    pub fsttype: String,
+    pub arctype: String,
+    pub version: i32,
+    pub flags: i32,
+    pub properties: u64,
+    pub start: i64,
+    pub numstates: i64,
+    pub numarcs: i64,
+}
+
+/// Weight types corresponding to an OpenFst arc type, with their
+/// binary representation.
+pub trait OpenFstWeight: Weight {
+    fn arc_type() -> String;
+    /// Number of bytes in the binary representation
+    fn size() -> usize;
+    fn from_bytes(b: &[u8]) -> Self;
+    fn to_bytes(&self) -> Vec<u8>;
+}
+
+fn float_size<T: Float<T>>() -> usize {
+    if T::get_precision() == "32" { 4 } else { 8 }
+}
+
+// OpenFst represents `none()` (NoWeight) as NaN
+fn float_from_bytes<T: Float<T>>(b: &[u8]) -> Option<T> {
+    let v = if b.len() == 4 {
+        let mut a = [0u8; 4];
+        a.copy_from_slice(b);
+        f64::from(f32::from_le_bytes(a))
+    } else {
+        let mut a = [0u8; 8];
+        a.copy_from_slice(b);
+        f64::from_le_bytes(a)
+    };
+    if v.is_nan() {
+        None
+    } else {
+        Some(T::from_f64(v))
+    }
+}
+
+fn float_to_bytes<T: Float<T>>(v: Option<T>) -> Vec<u8> {
+    let v = v.map_or(f64::NAN, |v| v.to_f64());
+    if float_size::<T>() == 4 {
+        (v as f32).to_le_bytes().to_vec()
+    } else {
+        v.to_le_bytes().to_vec()
+    }
+}
+
+impl<T: Float<T>> OpenFstWeight for TropicalWeight<T> {
+    fn arc_type() -> String {
+        match T::get_precision() {
+            "32" => String::from("standard"),
+            p => format!("tropical{}", p),
+        }
+    }
+
+    fn size() -> usize {
+        float_size::<T>()
+    }
+
+    fn from_bytes(b: &[u8]) -> Self {
+        Self::new(float_from_bytes(b))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        float_to_bytes(self.value())
+    }
+}
+
+impl<T: Float<T>> OpenFstWeight for LogWeight<T> {
+    fn arc_type() -> String {
+        match T::get_precision() {
+            "32" => String::from("log"),
+            p => format!("log{}", p),
+        }
+    }
+
+    fn size() -> usize {
+        float_size::<T>()
+    }
+
+    fn from_bytes(b: &[u8]) -> Self {
+        Self::new(float_from_bytes(b))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        float_to_bytes(self.value())
+    }
+}
+
+fn format_error(what: &str) -> Error {
+    Error::format(what)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+////////// READING
+////////////////////////////////////////////////////////////////////////////////
+
+struct Input<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Input<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if n > self.data.len() - self.pos {
+            return Err(format_error("unexpected end of OpenFst file"))
+        }
+        let b = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(b)
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    // Skips to the next multiple of `FILE_ALIGN` from the start
+    fn align(&mut self) -> Result<(), Error> {
+        let n = (FILE_ALIGN - self.pos % FILE_ALIGN) % FILE_ALIGN;
+        self.bytes(n).map(|_| ())
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let mut a = [0u8; 4];
+        a.copy_from_slice(self.bytes(4)?);
+        Ok(u32::from_le_bytes(a))
+    }
+
+    fn i32(&mut self) -> Result<i32, Error> {
+        self.u32().map(|v| v as i32)
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        let mut a = [0u8; 8];
+        a.copy_from_slice(self.bytes(8)?);
+        Ok(u64::from_le_bytes(a))
+    }
+
+    fn i64(&mut self) -> Result<i64, Error> {
+        self.u64().map(|v| v as i64)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let n = to_index(i64::from(self.i32()?), "string length")?;
+        Ok(String::from_utf8(self.bytes(n)?.to_vec())?)
+    }
+
+    fn weight<W: OpenFstWeight>(&mut self) -> Result<W, Error> {
+        Ok(W::from_bytes(self.bytes(W::size())?))
+    }
+
+    // ilabel, olabel, weight and nextstate
+    fn arc<W: OpenFstWeight>(&mut self) -> Result<(i32, i32, W, i32), Error> {
+        Ok((self.i32()?, self.i32()?, self.weight()?, self.i32()?))
+    }
+}
+
+fn to_index(v: i64, what: &str) -> Result<usize, Error> {
+    if v < 0 {
+        Err(format_error(&format!("negative {} ({})", what, v)))
+    } else {
+        Ok(v as usize)
+    }
+}
+
+fn read_header(input: &mut Input) -> Result<OpenFstHeader, Error> {
+    if input.i32()? != FST_MAGIC {
+        return Err(format_error("not an OpenFst file"))
+    }
+    Ok(OpenFstHeader{fsttype: input.string()?,
+                     arctype: input.string()?,
+                     version: input.i32()?,
+                     flags: input.i32()?,
+                     properties: input.u64()?,
+                     start: input.i64()?,
+                     numstates: input.i64()?,
+                     numarcs: input.i64()?})
+}
+
+fn read_symbols(input: &mut Input) -> Result<SymbolTable, Error> {
+    if input.i32()? != SYMBOLTABLE_MAGIC {
+        return Err(format_error("bad symbol table in OpenFst file"))
+    }
+    let name = input.string()?;
+    input.i64()?; // available key
+    let size = to_index(input.i64()?, "symbol table size")?;
+    let mut symtab = SymbolTable::new(&name);
+    for _ in 0..size {
+        let sym = input.string()?;
+        let label = to_index(input.i64()?, "label")?;
+        symtab.add_symbol_with_label(&sym, label);
+    }
+    Ok(symtab)
+}
+
+type States<W> = Vec<(W, Vec<(i32, i32, W, i32)>)>;
+
+fn read_vector<W: OpenFstWeight>(input: &mut Input, hdr: &OpenFstHeader) -> Result<States<W>, Error> {
+    if hdr.version < VECTOR_VERSION {
+        return Err(Error::Unsupported(format!("OpenFst vector Fst version {}", hdr.version)))
+    }
+    // The number of states is unknown (-1) if the file was written to a
+    // stream which could not be rewound
+    let mut states = Vec::new();
+    while if hdr.numstates < 0 { !input.at_end() } else { (states.len() as i64) < hdr.numstates } {
+        let finalweight = input.weight()?;
+        let narcs = to_index(input.i64()?, "number of arcs")?;
+        let mut arcs = Vec::new();
+        for _ in 0..narcs {
+            arcs.push(input.arc()?);
+        }
+        states.push((finalweight, arcs));
+    }
+    Ok(states)
+}
+
+fn read_const<W: OpenFstWeight>(input: &mut Input, hdr: &OpenFstHeader) -> Result<States<W>, Error> {
+    let aligned = hdr.flags & IS_ALIGNED != 0;
+    if hdr.version < CONST_ALIGNED_VERSION || (hdr.version < CONST_VERSION && !aligned) {
+        return Err(Error::Unsupported(format!("OpenFst const Fst version {}", hdr.version)))
+    }
+    let nstates = to_index(hdr.numstates, "number of states")?;
+    let narcs = to_index(hdr.numarcs, "number of arcs")?;
+    if aligned {
+        input.align()?;
+    }
+    // Final weight, position of first arc, number of arcs and of
+    // input/output epsilons
+    let mut spans = Vec::new();
+    for _ in 0..nstates {
+        let finalweight: W = input.weight()?;
+        let pos = input.u32()? as usize;
+        let n = input.u32()? as usize;
+        input.u32()?;
+        input.u32()?;
+        spans.push((finalweight, pos, n));
+    }
+    if aligned {
+        input.align()?;
+    }
+    // Arcs are stored as structs, padded to a multiple of the weight size
+    let padding = (W::size() - (12 + W::size()) % W::size()) % W::size();
+    let mut arcs = Vec::new();
+    for _ in 0..narcs {
+        arcs.push(input.arc()?);
+        input.bytes(padding)?;
+    }
+    let mut states = Vec::new();
+    for (finalweight, pos, n) in spans {
+        if pos + n > arcs.len() {
+            return Err(format_error("arc index out of range in OpenFst file"))
+        }
+        states.push((finalweight, arcs[pos..pos + n].to_vec()));
+    }
+    Ok(states)
+}
+
+/// Reads the header of an OpenFst file from `d`.
+pub fn header(d: &[u8]) -> Result<OpenFstHeader, Error> {
+    read_header(&mut Input{data: d, pos: 0})
+}
+
+/// Reads an OpenFst file from `d`. The arc type must correspond to `W`.
+pub fn read<W: OpenFstWeight, F: MutableFst<W>>(d: &[u8]) -> Result<F, Error> {
+    let mut input = Input{data: d, pos: 0};
+    let hdr = read_header(&mut input)?;
+    if hdr.arctype != W::arc_type() {
+        return Err(Error::UnsupportedWeight(format!("OpenFst arc type {} (expected {})", hdr.arctype, W::arc_type())))
+    }
+    let mut fst = F::new();
+    if hdr.flags & HAS_ISYMBOLS != 0 {
+        fst.set_isyms(read_symbols(&mut input)?);
+    }
+    if hdr.flags & HAS_OSYMBOLS != 0 {
+        fst.set_osyms(read_symbols(&mut input)?);
+    }
+    let states: States<W> = match hdr.fsttype.as_str() {
+        "vector" => read_vector(&mut input, &hdr)?,
+        "const" => read_const(&mut input, &hdr)?,
+        t => return Err(Error::Unsupported(format!("OpenFst type {}", t))),
+    };
+
+    let nstates = states.len();
+    fst.reserve_states(nstates);
+    for (finalweight, _) in &states {
+        fst.add_state(finalweight.clone());
+    }
+    if hdr.start >= 0 {
+        let start = to_index(hdr.start, "start state")?;
+        if start >= nstates {
+            return Err(format_error("start state out of range in OpenFst file"))
+        }
+        fst.set_start(start);
+    }
+    for (i, (_, arcs)) in states.into_iter().enumerate() {
+        fst.reserve_arcs(i, arcs.len());
+        for (ilabel, olabel, weight, nextstate) in arcs {
+            let nextstate = to_index(i64::from(nextstate), "state")?;
+            if nextstate >= nstates {
+                return Err(format_error("state out of range in OpenFst file"))
+            }
+            fst.add_arc(i,
+                        nextstate,
+                        to_index(i64::from(ilabel), "label")?,
+                        to_index(i64::from(olabel), "label")?,
+                        weight);
+        }
+    }
+    fst.set_properties((hdr.properties >> PROPERTIES_SHIFT) & FST_PROPERTIES, FST_PROPERTIES);
+    Ok(fst)
+}
+
+/// Loads an OpenFst file (see `read()`).
+pub fn load<W: OpenFstWeight, F: MutableFst<W>>(filename: &str) -> Result<F, Error> {
+    let mut d = Vec::new();
+    File::open(filename)?.read_to_end(&mut d)?;
+    read(&d)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+////////// WRITING
+////////////////////////////////////////////////////////////////////////////////
+
+fn put_u32(out: &mut Vec<u8>, v: u32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_i32(out: &mut Vec<u8>, v: i32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_u64(out: &mut Vec<u8>, v: u64) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_i64(out: &mut Vec<u8>, v: i64) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_string(out: &mut Vec<u8>, s: &str) -> Result<(), Error> {
+    put_i32(out, to_i32(s.len(), "string length")?);
+    out.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn to_i32(v: usize, what: &str) -> Result<i32, Error> {
+    if v > i32::MAX as usize {
+        Err(Error::Unsupported(format!("{} too large for OpenFst ({})", what, v)))
+    } else {
+        Ok(v as i32)
+    }
+}
+
+fn put_symbols(out: &mut Vec<u8>, symtab: &SymbolTable) -> Result<(), Error> {
+    put_i32(out, SYMBOLTABLE_MAGIC);
+    put_string(out, symtab.name())?;
+    put_i64(out, symtab.available_label() as i64);
+    put_i64(out, symtab.num_symbols() as i64);
+    for (label, sym) in symtab.iter() {
+        put_string(out, sym)?;
+        put_i64(out, *label as i64);
+    }
+    Ok(())
+}
+
+fn put_arc<W: OpenFstWeight, A: Arc<W>>(out: &mut Vec<u8>, arc: &A) -> Result<(), Error> {
+    put_i32(out, to_i32(arc.ilabel(), "label")?);
+    put_i32(out, to_i32(arc.olabel(), "label")?);
+    out.extend(arc.weight().to_bytes());
+    put_i32(out, to_i32(arc.nextstate(), "state")?);
+    Ok(())
+}
+
+/// Writes `fst` in the OpenFst format `fsttype` with the arc type
+/// corresponding to `W`.
+pub fn write<W: OpenFstWeight, F: ExpandedFst<W>>(fst: &F, fsttype: OpenFstType) -> Result<Vec<u8>, Error> {
+    let isyms = fst.get_isyms();
+    let osyms = fst.get_osyms();
+    let nstates = fst.get_numstates();
+    let narcs = fst.total_arcs();
+    let (version, properties) = match fsttype {
+        OpenFstType::Vector => (VECTOR_VERSION, (fst.properties(FST_PROPERTIES, false) << PROPERTIES_SHIFT) | EXPANDED | MUTABLE),
+        OpenFstType::Const => (CONST_VERSION, (fst.properties(FST_PROPERTIES, true) << PROPERTIES_SHIFT) | EXPANDED),
+    };
+    let mut flags = 0;
+    if isyms.is_some() {
+        flags |= HAS_ISYMBOLS;
+    }
+    if osyms.is_some() {
+        flags |= HAS_OSYMBOLS;
+    }
+
+    let mut out = Vec::new();
+    put_i32(&mut out, FST_MAGIC);
+    put_string(&mut out, fsttype.name())?;
+    put_string(&mut out, &W::arc_type())?;
+    put_i32(&mut out, version);
+    put_i32(&mut out, flags);
+    put_u64(&mut out, properties);
+    put_i64(&mut out, fst.get_start().map_or(-1, |s| s as i64));
+    put_i64(&mut out, nstates as i64);
+    put_i64(&mut out, narcs as i64);
+    if let Some(isyms) = isyms {
+        put_symbols(&mut out, &isyms)?;
+    }
+    if let Some(osyms) = osyms {
+        put_symbols(&mut out, &osyms)?;
+    }
+
+    match fsttype {
+        OpenFstType::Vector => {
+            for i in 0..nstates {
+                out.extend(fst.get_finalweight(i).to_bytes());
+                put_i64(&mut out, fst.num_arcs(i) as i64);
+                for arc in fst.arc_iter(i) {
+                    put_arc(&mut out, &arc)?;
+                }
+            }
+        },
+        OpenFstType::Const => {
+            if narcs > u32::MAX as usize {
+                return Err(Error::Unsupported(format!("too many arcs for OpenFst const Fst ({})", narcs)))
+            }
+            let mut pos = 0;
+            for i in 0..nstates {
+                out.extend(fst.get_finalweight(i).to_bytes());
+                put_u32(&mut out, pos as u32);
+                put_u32(&mut out, fst.num_arcs(i) as u32);
+                put_u32(&mut out, fst.num_input_epsilons(i) as u32);
+                put_u32(&mut out, fst.num_output_epsilons(i) as u32);
+                pos += fst.num_arcs(i);
+            }
+            let padding = (W::size() - (12 + W::size()) % W::size()) % W::size();
+            for i in 0..nstates {
+                for arc in fst.arc_iter(i) {
+                    put_arc(&mut out, &arc)?;
+                    out.extend(vec![0u8; padding]);
+                }
+            }
+        },
+    }
+    Ok(out)
+}
+
+/// Saves `fst` as an OpenFst file (see `write()`).
+pub fn save<W: OpenFstWeight, F: ExpandedFst<W>>(fst: &F, fsttype: OpenFstType, filename: &str) -> Result<(), Error> {
+    File::create(filename)?.write_all(&write(fst, fsttype)?)?;
+    Ok(())
+}
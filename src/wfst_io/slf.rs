@@ -0,0 +1,256 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements reading and writing of lattices in the HTK
+//! Standard Lattice Format (SLF), see "The HTK Book", chapter 20.
+//!
+//! Lattices are acceptors over words with `LatticeWeight`s. SLF
+//! language model (`l=`) and acoustic (`a=`) log scores become the
+//! graph and acoustic costs (negated and converted to natural logs
+//! according to `base=`). Other scores and times are ignored when
+//! reading.
+//!
+//! Words may be given on links or on nodes (labelling the links
+//! entering them). `!NULL` is read as epsilon (label 0). Without a
+//! symbol table, one is built from the words encountered (with
+//! `<eps>` as label 0). Use `algorithms::arcmap::LatticeScaleMapper`
+//! to combine the costs into `TropicalWeight`s.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+
+use super::super::Error;
+use super::text::MAX_STATE_GAP;
+use super::super::semiring::Weight;
+use super::super::semiring::float::Float;
+use super::super::semiring::latticeweight::LatticeWeight;
+use super::super::symboltable::SymbolTable;
+use super::super::{ExpandedFst, MutableFst, StateId, Label, Arc};
+
+/// The word written for epsilon labels
+pub const NULL_WORD: &str = "!NULL";
+
+fn line_error(lineno: usize, what: &str) -> Error {
+    Error::format_at(lineno, &format!("SLF: {}", what))
+}
+
+fn parse_field<V: ::std::str::FromStr>(fields: &HashMap<&str, &str>, names: &[&str], lineno: usize) -> Result<Option<V>, Error> {
+    match names.iter().filter_map(|name| fields.get(name)).next() {
+        Some(s) => s.parse().map(Some).map_err(|_| line_error(lineno, &format!("bad value '{}' for {}", s, names[0]))),
+        None => Ok(None),
+    }
+}
+
+// Nodes are created up to the largest id (as states in `text`), so ids
+// far beyond the number of lines are rejected rather than allocated
+fn check_node(node: usize, lineno: usize) -> Result<usize, Error> {
+    if node > 2 * lineno + MAX_STATE_GAP {
+        Err(line_error(lineno, &format!("node {} too large", node)))
+    } else {
+        Ok(node)
+    }
+}
+
+/// Reads a lattice in SLF from `reader`. Words are mapped to labels
+/// using `words` (which must contain all words) if given.
+pub fn read_slf<T, F, R>(reader: R, words: Option<Rc<SymbolTable>>) -> Result<F, Error>
+    where T: Float<T>,
+          F: MutableFst<LatticeWeight<T>>,
+          R: BufRead,
+{
+    let mut symtab = match words {
+        Some(ref words) => (**words).clone(),
+        None => {
+            let mut symtab = SymbolTable::new("");
+            symtab.add_symbol("<eps>");
+            symtab
+        },
+    };
+    let mut label = |word: Option<&str>, lineno: usize| -> Result<Label, Error> {
+        match word {
+            None | Some(NULL_WORD) => Ok(0),
+            Some(word) => match (words.is_some(), symtab.find_label(word)) {
+                (_, Some(label)) => Ok(label),
+                (false, None) => Ok(symtab.add_symbol(word)),
+                (true, None) => Err(line_error(lineno, &format!("word '{}' not in symbol table", word))),
+            },
+        }
+    };
+
+    // Multiplier to convert log scores to natural logs
+    let mut logbase = 1.0;
+    let mut startnode = None;
+    let mut endnode = None;
+    let mut nnodes = None;
+    let mut nodewords = HashMap::new();
+    let mut links = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let lineno = i + 1;
+        let line = line?;
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue
+        }
+        let fields: HashMap<&str, &str> = line.split_whitespace()
+            .map(|f| {
+                let mut kv = f.splitn(2, '=');
+                (kv.next().unwrap(), kv.next().unwrap_or("").trim_matches('"'))
+            }).collect();
+        let first = line.split_whitespace().next().unwrap().split('=').next().unwrap();
+        match first {
+            "I" => {
+                let node: StateId = check_node(parse_field(&fields, &["I"], lineno)?.unwrap(), lineno)?;
+                let word = fields.get("W").or_else(|| fields.get("WORD")).cloned();
+                nodewords.insert(node, word.map(String::from));
+            },
+            "J" => {
+                let src: StateId = parse_field(&fields, &["S", "START"], lineno)?
+                    .ok_or_else(|| line_error(lineno, "link without start node"))?;
+                let src = check_node(src, lineno)?;
+                let tgt: StateId = parse_field(&fields, &["E", "END"], lineno)?
+                    .ok_or_else(|| line_error(lineno, "link without end node"))?;
+                let tgt = check_node(tgt, lineno)?;
+                let word = fields.get("W").or_else(|| fields.get("WORD")).map(|w| String::from(*w));
+                let acoustic: f64 = parse_field(&fields, &["a", "acoustic"], lineno)?.unwrap_or(0.0);
+                let lm: f64 = parse_field(&fields, &["l", "language"], lineno)?.unwrap_or(0.0);
+                links.push((lineno, src, tgt, word, lm, acoustic));
+            },
+            "SUBLAT" => return Err(line_error(lineno, "sub-lattices are not supported")),
+            _ => {
+                if let Some(base) = parse_field::<f64>(&fields, &["base"], lineno)? {
+                    // Base 0 means that scores are not logs
+                    logbase = if base == 0.0 { 0.0 } else { base.ln() };
+                }
+                if let Some(n) = parse_field::<usize>(&fields, &["start"], lineno)? {
+                    startnode = Some(n);
+                }
+                if let Some(n) = parse_field::<usize>(&fields, &["end"], lineno)? {
+                    endnode = Some(n);
+                }
+                if let Some(n) = parse_field::<usize>(&fields, &["N", "NODES"], lineno)? {
+                    nnodes = Some(check_node(n, lineno)?);
+                }
+            },
+        }
+    }
+
+    let nnodes = match nnodes {
+        Some(n) => n,
+        None => nodewords.keys().chain(links.iter().flat_map(|l| vec![&l.1, &l.2])).max().map_or(0, |n| n + 1),
+    };
+    // Subtracting from zero avoids negative zero costs
+    let cost = |score: f64| -> T {
+        if logbase == 0.0 { T::from_f64(0.0 - score.ln()) } else { T::from_f64(0.0 - logbase * score) }
+    };
+    // The start (end) node defaults to the node without incoming
+    // (outgoing) links
+    let find_node = |n: Option<usize>, has_link: &dyn Fn(usize) -> bool, what: &str| -> Result<usize, Error> {
+        let n = match n {
+            Some(n) => n,
+            None => {
+                let candidates = (0..nnodes).filter(|i| !has_link(*i)).collect::<Vec<_>>();
+                if candidates.len() != 1 {
+                    return Err(Error::format(&format!("SLF: {} node not specified or unique", what)))
+                }
+                candidates[0]
+            },
+        };
+        if n >= nnodes {
+            return Err(Error::format(&format!("SLF: {} node {} out of range", what, n)))
+        }
+        Ok(n)
+    };
+    let startnode = find_node(startnode, &|i| links.iter().any(|l| l.2 == i), "start")?;
+    let endnode = find_node(endnode, &|i| links.iter().any(|l| l.1 == i), "end")?;
+
+    let mut arcs = Vec::with_capacity(links.len());
+    for (lineno, src, tgt, word, lm, acoustic) in links {
+        if src >= nnodes || tgt >= nnodes {
+            return Err(line_error(lineno, "node out of range"))
+        }
+        let word = word.or_else(|| nodewords.get(&tgt).cloned().and_then(|w| w));
+        arcs.push((src, tgt, label(word.as_deref(), lineno)?, LatticeWeight::from_costs(cost(lm), cost(acoustic))));
+    }
+
+    let mut fst = F::new();
+    let symtab = Rc::new(symtab);
+    fst.set_isyms(symtab.clone());
+    fst.set_osyms(symtab);
+    fst.reserve_states(nnodes);
+    for i in 0..nnodes {
+        fst.add_state(if i == endnode { LatticeWeight::one() } else { LatticeWeight::zero() });
+    }
+    fst.set_start(startnode);
+    for (src, tgt, label, weight) in arcs {
+        fst.add_arc(src, tgt, label, label, weight);
+    }
+    Ok(fst)
+}
+
+/// Writes `fst` in SLF to `writer`, with words (output labels) on the
+/// links. If `fst` does not have a single final state with final weight
+/// `one()` and no outgoing arcs, an end node is added with `!NULL`
+/// links from the final states carrying their final weights.
+pub fn write_slf<T, F, O>(fst: &F, writer: &mut O, utterance: Option<&str>) -> Result<(), Error>
+    where T: Float<T> + Display,
+          F: ExpandedFst<LatticeWeight<T>>,
+          O: Write,
+{
+    let startstate = fst.get_start().ok_or(Error::NoStartState)?;
+    let osyms = fst.get_osyms();
+    let word = |l: Label| -> String {
+        if l == 0 {
+            String::from(NULL_WORD)
+        } else {
+            match osyms {
+                Some(ref syms) => String::from(syms.find_symbol(l).unwrap_or("_")),
+                None => l.to_string(),
+            }
+        }
+    };
+    // Scores are negated costs
+    let link = |j: usize, src: StateId, tgt: StateId, w: String, weight: &LatticeWeight<T>| -> Result<String, Error> {
+        match weight.value() {
+            Some((g, a)) => Ok(format!("J={} S={} E={} W={} a={} l={}", j, src, tgt, w, T::zero() - a, T::zero() - g)),
+            None => Err(Error::Unsupported(String::from("undefined weights in SLF"))),
+        }
+    };
+
+    let nstates = fst.get_numstates();
+    let finalstates = (0..nstates).filter(|i| fst.is_final(*i)).collect::<Vec<_>>();
+    let single_end = finalstates.len() == 1 &&
+        fst.get_finalweight(finalstates[0]) == LatticeWeight::one() &&
+        fst.num_arcs(finalstates[0]) == 0;
+    let (nnodes, endnode) = if single_end { (nstates, finalstates[0]) } else { (nstates + 1, nstates) };
+    let nlinks = fst.total_arcs() + if single_end { 0 } else { finalstates.len() };
+
+    writeln!(writer, "VERSION=1.0")?;
+    if let Some(utterance) = utterance {
+        writeln!(writer, "UTTERANCE={}", utterance)?;
+    }
+    writeln!(writer, "start={} end={}", startstate, endnode)?;
+    writeln!(writer, "N={} L={}", nnodes, nlinks)?;
+    for i in 0..nnodes {
+        writeln!(writer, "I={}", i)?;
+    }
+    let mut j = 0;
+    for i in 0..nstates {
+        for arc in fst.arc_iter(i) {
+            writeln!(writer, "{}", link(j, i, arc.nextstate(), word(arc.olabel()), &arc.weight())?)?;
+            j += 1;
+        }
+    }
+    if !single_end {
+        for i in finalstates {
+            writeln!(writer, "{}", link(j, i, endnode, String::from(NULL_WORD), &fst.get_finalweight(i))?)?;
+            j += 1;
+        }
+    }
+    Ok(())
+}
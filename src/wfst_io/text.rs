@@ -0,0 +1,192 @@
+// Author: Daniel van Niekerk <dvn.demitasse@gmail.com>
+//
+// Copyright 2016 The Department of Arts and Culture of the Government
+// of South Africa
+//
+// See the "LICENCE" file for information on usage and redistribution
+// of this file.
+
+//! This module implements reading and writing of Fsts in the AT&T text
+//! format as used by OpenFst's `fstcompile` and `fstprint`.
+//!
+//! Each line describes either an arc (`src tgt ilabel olabel
+//! [weight]`) or a final state (`state [weight]`), with omitted weights
+//! equal to `W::one()`. Acceptors have a single label per arc (`src
+//! tgt label [weight]`). The source state of the first line is the
+//! start state. Labels are integers unless a symbol table is given.
+//!
+//! Text archives contain multiple Fsts, each preceded by a key on its
+//! own line and followed by an empty line (as in Kaldi).
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+
+use super::super::Error;
+use super::super::semiring::Weight;
+use super::super::symboltable::SymbolTable;
+use super::super::{ExpandedFst, MutableFst, StateId, Label, Arc};
+
+/// How far state ids may go beyond those of a dense numbering of the
+/// states on the lines read so far (at most two per line)
+pub const MAX_STATE_GAP: StateId = 1 << 20;
+
+fn line_error(lineno: usize, what: &str) -> Error {
+    Error::format_at(lineno, what)
+}
+
+// States are created up to the largest id, so ids far beyond the number
+// of lines are rejected rather than allocated
+fn parse_state(s: &str, lineno: usize) -> Result<StateId, Error> {
+    let state: StateId = s.parse().map_err(|_| line_error(lineno, &format!("bad state '{}'", s)))?;
+    if state > 2 * lineno + MAX_STATE_GAP {
+        return Err(line_error(lineno, &format!("state {} too large (all states up to the largest are created)", state)))
+    }
+    Ok(state)
+}
+
+fn parse_label(s: &str, syms: &Option<Rc<SymbolTable>>, lineno: usize) -> Result<Label, Error> {
+    match *syms {
+        Some(ref syms) => syms.find_label(s).ok_or_else(|| line_error(lineno, &format!("symbol '{}' not in symbol table '{}'", s, syms.name()))),
+        None => s.parse().map_err(|_| line_error(lineno, &format!("bad label '{}'", s))),
+    }
+}
+
+fn parse_weight<W: Weight>(s: &str, lineno: usize) -> Result<W, Error> {
+    W::parse_weight(s).map_err(|e| line_error(lineno, &e.to_string()))
+}
+
+/// Reads an Fst in AT&T text format from `reader`. If symbol tables are
+/// given they are used to map labels and set on the Fst, for acceptors
+/// `isyms` is used for both input and output labels. State ids are kept,
+/// and must not exceed twice the line number plus `MAX_STATE_GAP`.
+pub fn read_att<W, F, R>(reader: R, isyms: Option<Rc<SymbolTable>>, osyms: Option<Rc<SymbolTable>>, acceptor: bool) -> Result<F, Error>
+    where W: Weight,
+          F: MutableFst<W>,
+          R: BufRead,
+{
+    let osyms = if acceptor { isyms.clone() } else { osyms };
+    let nlabels = if acceptor { 1 } else { 2 };
+
+    let mut startstate = None;
+    let mut nstates = 0;
+    let mut arcs = Vec::new();
+    let mut finalweights = HashMap::new();
+    for (i, line) in reader.lines().enumerate() {
+        let lineno = i + 1;
+        let line = line?;
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        if fields.is_empty() {
+            continue
+        }
+        let src = parse_state(fields[0], lineno)?;
+        if startstate.is_none() {
+            startstate = Some(src);
+        }
+        nstates = nstates.max(src + 1);
+        match fields.len() {
+            1 => { finalweights.insert(src, W::one()); },
+            2 => { finalweights.insert(src, parse_weight(fields[1], lineno)?); },
+            n if n == 2 + nlabels || n == 3 + nlabels => {
+                let tgt = parse_state(fields[1], lineno)?;
+                nstates = nstates.max(tgt + 1);
+                let ilabel = parse_label(fields[2], &isyms, lineno)?;
+                let olabel = if acceptor { ilabel } else { parse_label(fields[3], &osyms, lineno)? };
+                let weight = if n == 3 + nlabels { parse_weight(fields[n - 1], lineno)? } else { W::one() };
+                arcs.push((src, tgt, ilabel, olabel, weight));
+            },
+            n => return Err(line_error(lineno, &format!("wrong number of fields ({})", n))),
+        }
+    }
+
+    let mut fst = F::new();
+    if let Some(isyms) = isyms {
+        fst.set_isyms(isyms);
+    }
+    if let Some(osyms) = osyms {
+        fst.set_osyms(osyms);
+    }
+    fst.reserve_states(nstates);
+    for i in 0..nstates {
+        fst.add_state(finalweights.remove(&i).unwrap_or_else(W::zero));
+    }
+    if let Some(startstate) = startstate {
+        fst.set_start(startstate);
+    }
+    let mut narcs = vec![0; nstates];
+    for arc in &arcs {
+        narcs[arc.0] += 1;
+    }
+    for (i, n) in narcs.into_iter().enumerate() {
+        fst.reserve_arcs(i, n);
+    }
+    for (src, tgt, ilabel, olabel, weight) in arcs {
+        fst.add_arc(src, tgt, ilabel, olabel, weight);
+    }
+    Ok(fst)
+}
+
+/// Writes `fst` in AT&T text format to `writer`, starting with the
+/// start state. Labels are written as symbols if the Fst has symbol
+/// tables (`_` if missing from the table). For acceptors only input
+/// labels are written.
+pub fn write_att<W, F, O>(fst: &F, writer: &mut O, acceptor: bool) -> Result<(), Error>
+    where W: Weight + Display,
+          F: ExpandedFst<W>,
+          O: Write,
+{
+    fn label(syms: &Option<Rc<SymbolTable>>, l: Label) -> String {
+        match *syms {
+            Some(ref syms) => String::from(syms.find_symbol(l).unwrap_or("_")),
+            None => l.to_string(),
+        }
+    }
+    let isyms = fst.get_isyms();
+    let osyms = fst.get_osyms();
+    let startstate = fst.get_start();
+    let states = startstate.into_iter().chain((0..fst.get_numstates()).filter(|i| Some(*i) != startstate));
+    for i in states {
+        for arc in fst.arc_iter(i) {
+            write!(writer, "{}\t{}\t{}", i, arc.nextstate(), label(&isyms, arc.ilabel()))?;
+            if !acceptor {
+                write!(writer, "\t{}", label(&osyms, arc.olabel()))?;
+            }
+            //Don't output weight field if equal to W::one
+            let weight = arc.weight();
+            if weight == W::one() {
+                writeln!(writer)?;
+            } else {
+                writeln!(writer, "\t{}", weight)?;
+            }
+        }
+        if fst.is_final(i) {
+            let weight = fst.get_finalweight(i);
+            if weight == W::one() {
+                writeln!(writer, "{}", i)?;
+            } else {
+                writeln!(writer, "{}\t{}", i, weight)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Splits a text archive into `(key, Fst text)` entries
+pub fn split_text_archive(text: &str) -> Vec<(String, String)> {
+    let mut entries: Vec<(String, String)> = Vec::new();
+    let mut in_entry = false;
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            in_entry = false;
+        } else if in_entry {
+            let entry = entries.last_mut().unwrap();
+            entry.1.push_str(line);
+            entry.1.push('\n');
+        } else {
+            entries.push((String::from(line.trim()), String::new()));
+            in_entry = true;
+        }
+    }
+    entries
+}
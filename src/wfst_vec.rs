@@ -19,7 +19,11 @@ use super::semiring::Weight;
 
 use std::fmt;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+
+use super::properties::{self, Properties};
+use super::symboltable::SymbolTable;
+use super::wfst_io::text::write_att;
 
 ////////// ARC
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
@@ -91,14 +95,101 @@ impl<W: Weight> Iterator for VecArcIterator<W> {
     }
 }
 
+/// Wraps an arc stored in a `VecFst` to allow in-place modification
+/// (see `MutableFst::arc_iter_mut()`).
+#[derive(PartialEq, Clone, Debug)]
+pub struct VecArcMut<'a, W: Weight> {
+    arc: Rc<RefCell<StdArc<W>>>,
+    source: StateId,
+    numstates: usize,
+    properties: &'a Cell<Properties>
+}
+
+impl<'a, W: Weight> VecArcMut<'a, W> {
+    fn update_properties(&self) {
+        let props = properties::set_arc_properties(self.properties.get(), self.source, &*self.arc.borrow());
+        self.properties.set(props);
+    }
+}
+
+impl<'a, W: Weight> Arc<W> for VecArcMut<'a, W> {
+    fn ilabel(&self) -> Label {
+        self.arc.ilabel()
+    }
+    fn olabel(&self) -> Label {
+        self.arc.olabel()
+    }
+    fn weight(&self) -> W {
+        self.arc.weight()
+    }
+    fn nextstate(&self) -> StateId {
+        self.arc.nextstate()
+    }
+}
+
+impl<'a, W: Weight> MutableArc<W> for VecArcMut<'a, W> {
+    fn set_ilabel(&mut self, ilabel: Label) {
+        self.arc.borrow_mut().ilabel = ilabel;
+        self.update_properties();
+    }
+    fn set_olabel(&mut self, olabel: Label) {
+        self.arc.borrow_mut().olabel = olabel;
+        self.update_properties();
+    }
+    fn set_weight(&mut self, weight: W) {
+        self.arc.borrow_mut().weight = weight;
+        self.update_properties();
+    }
+    fn set_nextstate(&mut self, nextstate: StateId) {
+        assert!(nextstate < self.numstates);
+        self.arc.borrow_mut().nextstate = nextstate;
+        self.update_properties();
+    }
+    fn try_set_nextstate(&mut self, nextstate: StateId) -> Result<(), Error> {
+        if nextstate >= self.numstates {
+            return Err(Error::InvalidState(nextstate))
+        }
+        self.set_nextstate(nextstate);
+        Ok(())
+    }
+}
+
+/// Iterates over the arcs of a state of a `VecFst` for in-place
+/// modification, keeping the Fst borrowed mutably so that states
+/// cannot be added or deleted meanwhile.
+#[derive(Debug)]
+pub struct VecArcIteratorMut<'a, W: Weight> {
+    iter: VecArcIterator<W>,
+    source: StateId,
+    numstates: usize,
+    properties: &'a Cell<Properties>
+}
+
+impl<'a, W: Weight> Iterator for VecArcIteratorMut<'a, W> {
+    type Item = VecArcMut<'a, W>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (source, numstates, properties) = (self.source, self.numstates, self.properties);
+        self.iter.next().map(|arc| VecArcMut { arc, source, numstates, properties })
+    }
+}
+
 
 ////////// STATE
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct VecState<W: Weight> {
     finalweight: W,
     arcs: Vec<Rc<RefCell<StdArc<W>>>>
 }
 
+// Arcs are copied rather than shared
+impl<W: Weight> Clone for VecState<W> {
+    fn clone(&self) -> Self {
+        VecState { finalweight: self.finalweight.clone(),
+                   arcs: self.arcs.iter().map(|arc| Rc::new(RefCell::new(arc.borrow().clone()))).collect() }
+    }
+}
+
 impl<W: Weight> VecState<W> {
     fn new(finalweight: W) -> VecState<W> {
         VecState { finalweight: finalweight,
@@ -107,12 +198,14 @@ impl<W: Weight> VecState<W> {
 }
 
 ////////// FST
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct VecFst<W: Weight> {
     states: Vec<Rc<RefCell<VecState<W>>>>,   //we need to make sure that element indexes are always consistent with arcs
     startstate: Option<usize>,
-    isyms: Option<Vec<String>>,
-    osyms: Option<Vec<String>>,
+    isyms: Option<Rc<SymbolTable>>,
+    osyms: Option<Rc<SymbolTable>>,
+    #[serde(skip)]
+    properties: Cell<Properties>,   //known properties, updated conservatively when mutated
 }
 
 impl<W: Weight> VecFst<W> {
@@ -120,14 +213,51 @@ impl<W: Weight> VecFst<W> {
         VecFst { states: Vec::new(),
                  startstate: None,
                  isyms: None,
-                 osyms: None }
+                 osyms: None,
+                 properties: Cell::new(properties::NULL_PROPERTIES) }
+    }
+
+    fn update_properties<F: FnOnce(Properties) -> Properties>(&self, f: F) {
+        self.properties.set(f(self.properties.get()));
+    }
+}
+
+/// The layout of a `VecFst` in native files written before format
+/// version 1, in which symbol tables were lists of symbols indexed by
+/// label (see `wfst_io::deserialise()`).
+#[derive(Debug, Deserialize)]
+pub struct LegacyVecFst<W: Weight> {
+    states: Vec<Rc<RefCell<VecState<W>>>>,
+    startstate: Option<usize>,
+    isyms: Option<Vec<String>>,
+    osyms: Option<Vec<String>>,
+}
+
+impl<W: Weight> From<LegacyVecFst<W>> for VecFst<W> {
+    fn from(fst: LegacyVecFst<W>) -> Self {
+        VecFst { states: fst.states,
+                 startstate: fst.startstate,
+                 isyms: fst.isyms.map(|syms| Rc::new(syms.into_iter().collect())),
+                 osyms: fst.osyms.map(|syms| Rc::new(syms.into_iter().collect())),
+                 properties: Cell::new(0) }
+    }
+}
+
+// States and arcs are copied rather than shared so that mutating a
+// clone does not affect the original (symbol tables are shared)
+impl<W: Weight> Clone for VecFst<W> {
+    fn clone(&self) -> Self {
+        VecFst { states: self.states.iter().map(|state| Rc::new(RefCell::new(state.borrow().clone()))).collect(),
+                 startstate: self.startstate,
+                 isyms: self.isyms.clone(),
+                 osyms: self.osyms.clone(),
+                 properties: self.properties.clone() }
     }
 }
 
 impl<W: Weight> Fst<W> for VecFst<W> {
     type Arc = Rc<RefCell<StdArc<W>>>;
     type Iter = VecArcIterator<W>;
-    type Symtab = Vec<String>;
 
     fn get_start(&self) -> Option<StateId> {
         self.startstate
@@ -142,20 +272,27 @@ impl<W: Weight> Fst<W> for VecFst<W> {
                          arcindex: 0 }
     }
 
-    fn get_isyms(&self) -> Option<Self::Symtab> {
+    fn get_isyms(&self) -> Option<Rc<SymbolTable>> {
         self.isyms.clone()
     }
 
-    fn get_osyms(&self) -> Option<Self::Symtab> {
+    fn get_osyms(&self) -> Option<Rc<SymbolTable>> {
         self.osyms.clone()
     }
 
     fn is_final(&self, id: StateId) -> bool {
         self.get_finalweight(id).ne(&W::zero())
     }
+
+    fn fst_type() -> String {
+        String::from("vector")
+    }
 }
 
 impl<W: Weight> MutableFst<W> for VecFst<W> {  
+    type ArcMut<'a> = VecArcMut<'a, W> where W: 'a;
+    type IterMut<'a> = VecArcIteratorMut<'a, W> where W: 'a;
+
     fn new() -> Self {
         VecFst::new()
     }
@@ -163,73 +300,128 @@ impl<W: Weight> MutableFst<W> for VecFst<W> {
     fn set_start(&mut self, id: StateId) {
         assert!(id < self.states.len());
         self.startstate = Some(id);
+        self.update_properties(|p| p & !(properties::ACCESSIBLE | properties::NOT_ACCESSIBLE |
+                                         properties::INITIAL_CYCLIC | properties::INITIAL_ACYCLIC));
     }
 
     fn add_state(&mut self, finalweight: W) -> StateId {
         let id = self.states.len();
+        self.update_properties(|p| properties::add_state_properties(p, &finalweight));
         self.states.push(Rc::new(RefCell::new(VecState::new(finalweight))));
         id
     }
 
     fn del_state(&mut self, id: StateId) {
-        assert!(id != self.startstate.unwrap());
-        self.states.remove(id);
-        //update arcs in remaining states
-        for i in 0..self.states.len() {
-            let narcs = self.states[i].borrow().arcs.len();
-            let mut to_delete = Vec::<usize>::new();
-            let mut state = self.states[i].borrow_mut();
-            for j in 0..narcs {
-                let nextstate = state.arcs[j].borrow().nextstate;
-                if nextstate == id {
-                    to_delete.push(j)
-                } else if nextstate > id {
-                    state.arcs[j].borrow_mut().nextstate -= 1;
-                }
-            }
-            to_delete.sort();
-            for j in (0..to_delete.len()).rev() {
-                state.arcs.remove(to_delete[j]);
-            }
-        }
+        self.del_states(Some(id));
     }
 
+    /// Deletes all the given states in a single pass: remaining states
+    /// are renumbered in order and arcs into deleted states are
+    /// dropped. The start state is unset if it is deleted.
     fn del_states<T: IntoIterator<Item=StateId>>(&mut self, states: T) {
-        let mut v: Vec<_> = states.into_iter().collect();
-        v.sort();
-        for j in (0..v.len()).rev() {
-            self.del_state(v[j]);
+        let nstates = self.states.len();
+        let mut keep = vec![true; nstates];
+        for id in states {
+            assert!(id < nstates);
+            keep[id] = false;
+        }
+        //map old to new state ids
+        let mut newids: Vec<Option<StateId>> = Vec::with_capacity(nstates);
+        let mut n: usize = 0;
+        for k in &keep {
+            if *k {
+                newids.push(Some(n));
+                n += 1;
+            } else {
+                newids.push(None);
+            }
         }
+        if n == nstates {
+            return
+        }
+        self.update_properties(|p| p & properties::DELETE_STATES_PROPERTIES);
+        //drop deleted states and rewrite arcs in remaining states
+        let states = ::std::mem::replace(&mut self.states, Vec::with_capacity(n));
+        for (state, k) in states.into_iter().zip(keep) {
+            if !k {
+                continue
+            }
+            {
+                let mut st = state.borrow_mut();
+                st.arcs.retain(|arc| newids[arc.borrow().nextstate].is_some());
+                for arc in &st.arcs {
+                    let mut arc = arc.borrow_mut();
+                    arc.nextstate = newids[arc.nextstate].unwrap();
+                }
+            }
+            self.states.push(state);
+        }
+        self.startstate = self.startstate.and_then(|s| newids[s]);
     }
 
     fn add_arc(&mut self, source: StateId, target: StateId, ilabel: Label, olabel: Label, weight: W) {
         assert!(source < self.states.len());
         assert!(target < self.states.len());
-        self.states[source]
-            .borrow_mut()
-            .arcs
-            .push(Rc::new(RefCell::new(StdArc::new(ilabel, olabel, weight, target))))
+        let arc = Rc::new(RefCell::new(StdArc::new(ilabel, olabel, weight, target)));
+        let mut state = self.states[source].borrow_mut();
+        self.update_properties(|p| properties::add_arc_properties(p, source, &arc, state.arcs.last()));
+        state.arcs.push(arc)
+    }
+
+    fn set_arc(&mut self, source: StateId, index: usize, target: StateId, ilabel: Label, olabel: Label, weight: W) {
+        assert!(source < self.states.len());
+        assert!(target < self.states.len());
+        let state = self.states[source].borrow();
+        assert!(index < state.arcs.len());
+        let arc = StdArc::new(ilabel, olabel, weight, target);
+        self.update_properties(|p| properties::set_arc_properties(p, source, &arc));
+        *state.arcs[index].borrow_mut() = arc;
+    }
+
+    fn del_arc(&mut self, source: StateId, index: usize) {
+        assert!(source < self.states.len());
+        let mut state = self.states[source].borrow_mut();
+        assert!(index < state.arcs.len());
+        self.update_properties(|p| p & properties::DELETE_ARCS_PROPERTIES);
+        state.arcs.remove(index);
+    }
+
+    fn del_arcs(&mut self, source: StateId) {
+        assert!(source < self.states.len());
+        self.update_properties(|p| p & properties::DELETE_ARCS_PROPERTIES);
+        self.states[source].borrow_mut().arcs.clear();
+    }
+
+    fn arc_iter_mut(&mut self, source: StateId) -> Self::IterMut<'_> {
+        //properties are updated as each arc is changed
+        VecArcIteratorMut { iter: self.arc_iter(source),
+                            source,
+                            numstates: self.states.len(),
+                            properties: &self.properties }
+    }
+
+    fn reserve_states(&mut self, n: usize) {
+        self.states.reserve(n);
+    }
+
+    fn reserve_arcs(&mut self, source: StateId, n: usize) {
+        assert!(source < self.states.len());
+        self.states[source].borrow_mut().arcs.reserve(n);
     }
 
     fn set_finalweight(&mut self, id: StateId, finalweight: W) {
         assert!(id < self.states.len());
-        self.states[id].borrow_mut().finalweight = finalweight;
+        let mut state = self.states[id].borrow_mut();
+        self.update_properties(|p| properties::set_final_properties(p, &state.finalweight, &finalweight));
+        state.finalweight = finalweight;
     }
 
-    fn set_isyms<T: IntoIterator<Item=String>>(&mut self, symtab: T) {
-        let mut v = Vec::new();
-        for s in symtab {
-            v.push(s)
-        }
-        self.isyms = Some(v)
+    fn set_isyms<T: Into<Rc<SymbolTable>>>(&mut self, symtab: T) {
+        self.isyms = Some(symtab.into())
     }
 
-    fn set_osyms<T: IntoIterator<Item=String>>(&mut self, symtab: T) {
-        let mut v = Vec::new();
-        for s in symtab {
-            v.push(s)
-        }
-        self.osyms = Some(v)
+    fn set_osyms<T: Into<Rc<SymbolTable>>>(&mut self, symtab: T) {
+        self.osyms = Some(symtab.into())
     }
 
     fn del_isyms(&mut self) {
@@ -239,67 +431,46 @@ impl<W: Weight> MutableFst<W> for VecFst<W> {
     fn del_osyms(&mut self) {
         self.osyms = None;
     }
+
+    fn set_properties(&mut self, props: Properties, mask: Properties) {
+        let mask = properties::known_properties(mask);
+        self.update_properties(|p| (p & !mask) | (props & mask));
+    }
 }
 
 impl<W: Weight> ExpandedFst<W> for VecFst<W> {  
     fn get_numstates(&self) -> usize {
         self.states.len()
     }
+
+    fn properties(&self, mask: Properties, test: bool) -> Properties {
+        let known = self.properties.get();
+        if test && !properties::properties_known(known, mask) {
+            let props = properties::compute_properties(self, mask);
+            let computed = properties::known_properties(props);
+            self.properties.set((known & !computed) | props);
+        }
+        self.properties.get() & properties::known_properties(mask)
+    }
+
+    fn num_arcs(&self, id: StateId) -> usize {
+        self.states[id].borrow().arcs.len()
+    }
+
+    fn num_input_epsilons(&self, id: StateId) -> usize {
+        self.states[id].borrow().arcs.iter().filter(|arc| arc.borrow().ilabel == 0).count()
+    }
+
+    fn num_output_epsilons(&self, id: StateId) -> usize {
+        self.states[id].borrow().arcs.iter().filter(|arc| arc.borrow().olabel == 0).count()
+    }
 }
 
+/// Formats in AT&T text format (see `wfst_io::text::write_att()`)
 impl<W: Weight + fmt::Display> fmt::Display for VecFst<W> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fn helper<W: Weight + fmt::Display>(f: &VecFst<W>, i: usize, mut s: String) -> String {
-            let narcs = f.states[i].borrow().arcs.len();
-            let state = f.states[i].borrow();
-            for j in 0..narcs {
-                s.push_str(&format!("{}\t{}", i, state.arcs[j].borrow().nextstate));
-                //String input label if mapping exists (output `_` if map not complete)
-                if let Some(ref syms) = f.isyms {
-                    s.push_str(&format!("\t{}", syms.get(state.arcs[j].borrow().ilabel).unwrap_or(&String::from("_"))));
-                } else {
-                    s.push_str(&format!("\t{}", state.arcs[j].borrow().ilabel));
-                }
-                //String output label if mapping exists (output `_` if map not complete)
-                if let Some(ref syms) = f.osyms {
-                    s.push_str(&format!("\t{}", syms.get(state.arcs[j].borrow().olabel).unwrap_or(&String::from("_"))));
-                } else {
-                    s.push_str(&format!("\t{}", state.arcs[j].borrow().olabel));
-                }
-                //Don't output weight field if equal to W::one
-                let weight = &state.arcs[j].borrow().weight;
-                if *weight == W::one() {
-                    s.push_str(&format!("\n"));
-                } else {
-                    s.push_str(&format!("\t{}\n", *weight));
-                }         
-            }
-            if f.is_final(i) {
-                s.push_str(&format!("{}", i));
-                //Don't output weight field if equal to W::one
-                let weight = f.get_finalweight(i);
-                if weight == W::one() {
-                    s.push_str(&format!("\n"));
-                } else {
-                    s.push_str(&format!("\t{}\n", weight));
-                }
-            }
-            s
-        }
-        let mut s = String::new();
-        //Start state must be in first line
-        if let Some(startstate) = self.startstate {
-            s = helper(self, startstate, s);
-        }
-        //Remaining states
-        for i in 0..self.states.len() {
-            if !self.startstate.is_none() {
-                if i == self.startstate.unwrap() {
-                    continue
-                }
-            }
-            s = helper(self, i, s);
-        }
-        write!(f, "{}", s.trim())
+        let mut buf = Vec::new();
+        write_att(self, &mut buf, false).map_err(|_| fmt::Error)?;
+        write!(f, "{}", String::from_utf8_lossy(&buf).trim())
     }
 }
@@ -1,186 +1,96 @@
 extern crate argparse;
-use argparse::{ArgumentParser, StoreTrue, StoreOption};
+use argparse::{ArgumentParser, Store, StoreTrue, StoreOption};
 
 extern crate wfst;
-use wfst::semiring::float::Float;
-use wfst::semiring::floatweight::{FloatWeight, TropicalWeight, LogWeight, MinmaxWeight};
+use wfst::semiring::Weight;
+use wfst::semiring::floatweight::{TropicalWeight, LogWeight, MinmaxWeight, RealWeight, ArcticWeight};
+use wfst::semiring::booleanweight::BooleanWeight;
 use wfst::wfst_vec::{VecFst};
-use wfst::{MutableFst};
+use wfst::{Fst, MutableFst};
+use wfst::symboltable::SymbolTable;
 
-use wfst::wfst_io::{serialise, IOError};//, deserialise};
+use wfst::Error;
+use wfst::wfst_io::serialise_into;
+use wfst::wfst_io::text::{read_att, split_text_archive};
+use wfst::wfst_io::archive::ArchiveWriter;
+use wfst::wfst_io::compression::{Compression, CompressedWriter, decompress};
 
-use std::fmt::Debug;
-use std::str::FromStr;
-use std::error::Error;
-use std::fs::File;
-use std::io::{self, Read, Write, BufRead};
+use std::io::{self, BufRead, Read};
 use std::process::exit;
-use std::collections::HashMap;
+use std::rc::Rc;
 
 extern crate serde;
 use serde::Serialize;
-use serde::de::DeserializeOwned;
 
 const EXCODE_BADINPUT: i32 = 2;
 
-//DEMIT TODO: Remove duplication
-fn load_symtab(symfn: String) -> Result<Vec<String>, IOError> {
-    //Slurp lines to parsed fields (DEMITMEM)
-    let mut fh = File::open(symfn)?;
-    let mut s = String::new();
-    fh.read_to_string(&mut s)?;
-    let mut entries = Vec::new();
-    let mut n: usize = 0;
-    for line in s.lines() {
-        let fields = line.split_whitespace().collect::<Vec<_>>();
-        if fields.len() != 2 {
-            return Err(IOError{message: format!("Format error: wrong number of fields")})
-        }
-        let entry = (fields[1].parse::<usize>()?, String::from(fields[0]));
-        if entry.0 > n {
-            n = entry.0;
-        }
-        entries.push(entry)
-    }
-    let mut syms = Vec::with_capacity(n+1);
-    for _ in 0..n+1 {
-        syms.push(String::new());
-    }
-    for entry in entries {
-        syms[entry.0] = entry.1;
-    }
-    Ok(syms)
-}
-
-fn load_set_syms<T, W, F>(symfn: Option<String>, fst: &mut F, mapsyms: bool, insym: bool) -> Result<Option<HashMap<String, usize>>, IOError>
-    where T: Float<T>,
-          W: FloatWeight<T>,
-          F: MutableFst<W>,
-{
+fn load_syms(symfn: Option<String>, mapsyms: bool) -> Result<Option<Rc<SymbolTable>>, Error> {
     if let Some(tempfn) = symfn {
-        match load_symtab(tempfn) {
-            Ok(syms) =>
-            {
-                if insym {
-                    fst.set_isyms(syms.clone());
-                } else {
-                    fst.set_osyms(syms.clone());
-                }
-                let symtab: HashMap<String, usize> = syms.into_iter().enumerate().map(|x| (x.1, x.0)).collect();
-                if mapsyms {
-                    Ok(Some(symtab))
-                } else {
-                    Ok(None)
-                }
-            },
-            Err(e) => Err(IOError{message: e.message}),
-        }
+        Ok(Some(Rc::new(SymbolTable::load(&tempfn)?)))
+    } else if mapsyms {
+        Err(Error::Invalid(String::from("cannot map symbols without specifying a table file")))
     } else {
-        if mapsyms {
-            Err(IOError{message: format!("CLI Option error: cannot map input symbols without specifying a table file")})
-        } else {
-            Ok(None)
-        }
+        Ok(None)
     }
 }
 
-//DEMIT TODO: This should all be moved to relevant implementations of FromStr at specific Fst and Weight implementations
-fn input<T, W, F>(mut fst: F, isymfn: Option<String>, osymfn: Option<String>, mapisyms: bool, maposyms: bool) -> Result<F, IOError>
-    where T: Float<T> + FromStr,
-          T::Err: Error + Debug,
-          W: FloatWeight<T>,
+fn input<W, F>(isymfn: Option<String>, osymfn: Option<String>, mapisyms: bool, maposyms: bool, acceptor: bool, archive: bool) -> Result<Vec<(String, F)>, Error>
+    where W: Weight,
           F: MutableFst<W>,
 {
-    ////Possibly load/apply symbol tables
-    let isymtab = load_set_syms(isymfn, &mut fst, mapisyms, true)?;
-    let osymtab = load_set_syms(osymfn, &mut fst, maposyms, false)?;
-    //eprintln!("{:?}", isymtab);
-    //eprintln!("{:?}", osymtab);
-    
-    ////Parse input from STDIN
-    let stdin = io::stdin();
-    let handle = stdin.lock();    //for (fast) buffered input
-    let mut is_start = true;
-    let mut arcs = Vec::new();
-    let mut nstates: usize = 0;
-    let mut startstate: usize = 0;
-    let mut finalstates = HashMap::new();
-    for l in handle.lines() {     //strips newlines
-        let line: String = l.unwrap();
-        //eprintln!("{}", line);
-        let fields = line.split_whitespace().collect::<Vec<_>>();
-        let (is_final, weight) = match fields.len() {
-            1 => (true, W::one()),
-            2 => (true, W::new(Some(fields[1].parse()?))),
-            4 => (false, W::one()),
-            5 => (false, W::new(Some(fields[4].parse()?))),
-            _ => return Err(IOError{message: format!("Format error: wrong number of fields")})
-        };
-        //eprintln!("{:?} {:?} {:?}", is_start, is_final, weight);
-        if !is_final {
-            let src = fields[0].parse()?;
-            let tgt = fields[1].parse()?;
-            if src > nstates { nstates = src };
-            if tgt > nstates { nstates = tgt };
-            let ilabel = if let Some(ref symtab) = isymtab {
-                *symtab.get(fields[2]).ok_or(IOError{message: format!("Input error: symbol table does not contain 'string' symbol")})?
-            } else {
-                fields[2].parse()?
-            };
-            let olabel = if let Some(ref symtab) = osymtab {
-                *symtab.get(fields[3]).ok_or(IOError{message: format!("Input error: symbol table does not contain 'string' symbol")})?
-            } else {
-                fields[3].parse()?
-            };
-            arcs.push((src, tgt, ilabel, olabel, weight));
-        } else {
-            let tgt = fields[0].parse()?;
-            finalstates.insert(tgt, weight);
-            if tgt > nstates { nstates = tgt };
-        }            
-        if is_start {
-            startstate = fields[0].parse()?;
-            is_start = false;
+    ////Possibly load symbol tables (used to map labels or only set on the Fst)
+    let isymtab = load_syms(isymfn, mapisyms)?;
+    let osymtab = load_syms(osymfn, maposyms)?;
+    let compile = |text: &mut dyn BufRead| -> Result<F, Error> {
+        let mut fst: F = read_att(text,
+                                  if mapisyms { isymtab.clone() } else { None },
+                                  if maposyms { osymtab.clone() } else { None },
+                                  acceptor)?;
+        if let (Some(syms), false) = (isymtab.clone(), mapisyms) {
+            fst.set_isyms(syms);
         }
-    }
-    nstates += 1;
-    // eprintln!("nstates: {}", nstates);
-    // eprintln!("startstate: {}", startstate);
-    // eprintln!("finalstates: {:?}", finalstates);
-    // eprintln!("{:?}", arcs);
-    // eprintln!("{:?}", fst);
+        if let (Some(syms), false) = (osymtab.clone(), maposyms) {
+            fst.set_osyms(syms);
+        }
+        Ok(fst)
+    };
 
-    ////Construct FST
-    for i in 0..nstates {
-        if finalstates.contains_key(&i) {
-            fst.add_state(finalstates.remove(&i).unwrap());
-        } else {
-            fst.add_state(W::zero());
+    ////Parse input from STDIN (decompressing if necessary)
+    let stdin = io::stdin();
+    let mut reader = decompress(stdin.lock())?;
+    if archive {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        let mut fsts = Vec::new();
+        for (key, fsttext) in split_text_archive(&text) {
+            match compile(&mut fsttext.as_bytes()) {
+                Ok(fst) => fsts.push((key, fst)),
+                Err(e) => return Err(e.in_entry(&key)),
+            }
         }
+        Ok(fsts)
+    } else {
+        Ok(vec![(String::new(), compile(&mut reader)?)])
     }
-    fst.set_start(startstate);
-    for arc in arcs {
-        fst.add_arc(arc.0, arc.1, arc.2, arc.3, arc.4);
-    }
-    Ok(fst)
 }
 
-fn output<T: Serialize + DeserializeOwned + Debug + 'static>(t: Result<T, IOError>) -> Result<(), IOError> {
+fn output<W: Weight, F: Fst<W> + Serialize>(t: Result<Vec<(String, F)>, Error>, archive: bool, compression: Compression) -> Result<(), Error> {
     ////Output on STDOUT
-    match t {
-        Ok(tt) => {
-            //eprintln!("\n{:?}\n", tt);
-            //eprintln!("////////////////////////////////////////\n");
-            let encoded = serialise(&tt)?;
-            //////////
-            //let ww: T = deserialise(&encoded)?;
-            //eprintln!("{:?}\n", ww);
-            //////////
-            io::stdout().write(&*encoded).ok();
-            Ok(())
-        },
-        Err(e) => Err(e),
+    let fsts = t?;
+    let mut handle = CompressedWriter::new(io::stdout(), compression)?;
+    if archive {
+        let mut writer = ArchiveWriter::new(&mut handle)?;
+        for (key, fst) in &fsts {
+            writer.add(key, fst)?;
+        }
+        writer.finish()?;
+    } else {
+        for (_, fst) in &fsts {
+            serialise_into(fst, &mut handle)?;
+        }
     }
+    handle.finish()?;
+    Ok(())
 }
 
 
@@ -190,6 +100,9 @@ fn main() {
     let mut wtype: Option<usize> = None;
     let mut mapisyms = false;
     let mut maposyms = false;
+    let mut acceptor = false;
+    let mut archive = false;
+    let mut compression = Compression::None;
     let mut isymfn: Option<String> = None;
     let mut osymfn: Option<String> = None;
     { // this block limits scope of borrows by ap.refer() method
@@ -200,11 +113,17 @@ fn main() {
         ap.refer(&mut osymfn)
             .add_option(&["-o", "--osymfn"], StoreOption, "Output-label symbol table filename");
         ap.refer(&mut wtype)
-            .add_option(&["-w", "--wtype"], StoreOption, "Select the weight type (semiring) from (0: Tropical, 1: Log, 2: Minmax -- default is 0)");
+            .add_option(&["-w", "--wtype"], StoreOption, "Select the weight type (semiring) from (0: Tropical, 1: Log, 2: Minmax, 3: Real, 4: Arctic, 5: Boolean -- default is 0)");
         ap.refer(&mut mapisyms)
             .add_option(&["-I", "--strsin"], StoreTrue, "Map input symbols using symbol table (default is to read integer symbols)");
         ap.refer(&mut maposyms)
             .add_option(&["-O", "--strsout"], StoreTrue, "Map output symbols using symbol table (default is to read integer symbols)");
+        ap.refer(&mut acceptor)
+            .add_option(&["-a", "--acceptor"], StoreTrue, "Read an acceptor (a single label per arc)");
+        ap.refer(&mut archive)
+            .add_option(&["-A", "--archive"], StoreTrue, "Read a text archive (entries of a key line followed by an FST, separated by empty lines) and write an FST archive");
+        ap.refer(&mut compression)
+            .add_option(&["-z", "--compress"], Store, "Compress the output (none, gzip or zstd -- default is none)");
         ap.refer(&mut p64)
             .add_option(&["-p", "--precision"], StoreTrue, "Use 64-bit precision for weights (default is 32-bit)");
         ap.parse_args_or_exit();
@@ -213,25 +132,31 @@ fn main() {
     let semiring = wtype.unwrap_or(0);
     match if p64 {
         match semiring {
-            0 => output(input(VecFst::<TropicalWeight<f64>>::new(), isymfn, osymfn, mapisyms, maposyms)),
-            1 => output(input(VecFst::<LogWeight<f64>>::new(), isymfn, osymfn, mapisyms, maposyms)),
-            2 => output(input(VecFst::<MinmaxWeight<f64>>::new(), isymfn, osymfn, mapisyms, maposyms)),
+            0 => output(input::<_, VecFst<TropicalWeight<f64>>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
+            1 => output(input::<_, VecFst<LogWeight<f64>>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
+            2 => output(input::<_, VecFst<MinmaxWeight<f64>>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
+            3 => output(input::<_, VecFst<RealWeight<f64>>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
+            4 => output(input::<_, VecFst<ArcticWeight<f64>>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
+            5 => output(input::<_, VecFst<BooleanWeight>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
             _ => { eprintln!("Invalid weight type: {:?}", semiring);
                    exit(EXCODE_BADINPUT);
             },
         }
     } else {
         match semiring {
-            0 => output(input(VecFst::<TropicalWeight<f32>>::new(), isymfn, osymfn, mapisyms, maposyms)),
-            1 => output(input(VecFst::<LogWeight<f32>>::new(), isymfn, osymfn, mapisyms, maposyms)),
-            2 => output(input(VecFst::<MinmaxWeight<f32>>::new(), isymfn, osymfn, mapisyms, maposyms)),
+            0 => output(input::<_, VecFst<TropicalWeight<f32>>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
+            1 => output(input::<_, VecFst<LogWeight<f32>>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
+            2 => output(input::<_, VecFst<MinmaxWeight<f32>>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
+            3 => output(input::<_, VecFst<RealWeight<f32>>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
+            4 => output(input::<_, VecFst<ArcticWeight<f32>>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
+            5 => output(input::<_, VecFst<BooleanWeight>>(isymfn, osymfn, mapisyms, maposyms, acceptor, archive), archive, compression),
             _ => { eprintln!("Invalid weight type: {:?}", semiring);
                    exit(EXCODE_BADINPUT);
             },
         }
     } {
         Ok(_) => (),
-        Err(e) => { eprintln!("{}", e.message);
+        Err(e) => { eprintln!("{}", e);
                     exit(EXCODE_BADINPUT);
         },
     }
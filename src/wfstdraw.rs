@@ -0,0 +1,113 @@
+extern crate argparse;
+use argparse::{ArgumentParser, StoreTrue, StoreOption};
+
+#[macro_use]
+extern crate wfst;
+use wfst::semiring::Weight;
+use wfst::{MutableFst, ExpandedFst};
+use wfst::symboltable::SymbolTable;
+
+use std::fmt::Display;
+use std::io::{self, BufRead, Read, Cursor};
+use std::rc::Rc;
+use std::process::exit;
+
+use wfst::Error;
+use wfst::wfst_io::deserialise_wrapper_from;
+use wfst::wfst_io::compression::{decompress, peek};
+use wfst::wfst_io::dot::{write_dot, DotOptions};
+use wfst::wfst_io::archive::{is_archive, ArchiveReader, ARCHIVE_MAGIC};
+
+const EXCODE_BADINPUT: i32 = 2;
+
+fn wfstdraw<W: Weight + Display, F: MutableFst<W> + ExpandedFst<W>>(mut fst: F, isyms: Option<Rc<SymbolTable>>, osyms: Option<Rc<SymbolTable>>, options: &DotOptions) -> Result<(), Error> {
+
+    if let Some(syms) = isyms {
+        fst.set_isyms(syms);
+    }
+    if let Some(syms) = osyms {
+        fst.set_osyms(syms);
+    }
+
+    let stdout = io::stdout();
+    write_dot(&fst, &mut stdout.lock(), options)
+}
+
+//Draws each Fst in an archive as a separate graph, titled with its key
+//unless a title is given
+fn wfstdraw_archive(buffer: Vec<u8>, isyms: Option<Rc<SymbolTable>>, osyms: Option<Rc<SymbolTable>>, options: &DotOptions) -> Result<(), Error> {
+    let mut archive = ArchiveReader::new(Cursor::new(buffer))?;
+    for record in archive.iter() {
+        let (key, w) = record?;
+        let mut options = options.clone();
+        if options.title.is_empty() {
+            options.title = key;
+        }
+        wfstio_wrapper_apply!(w, fst, wfstdraw(fst, isyms.clone(), osyms.clone(), &options))?;
+    }
+    Ok(())
+}
+
+fn load_syms(symfn: Option<String>) -> Option<Rc<SymbolTable>> {
+    match symfn {
+        Some(symfn) => match SymbolTable::load(&symfn) {
+            Ok(syms) => Some(Rc::new(syms)),
+            Err(e) => { eprintln!("{}", e);
+                        exit(EXCODE_BADINPUT);
+            },
+        },
+        None => None,
+    }
+}
+
+fn main() {
+    //Setup defaults and parse args
+    let mut options = DotOptions::default();
+    let mut title: Option<String> = None;
+    let mut isymfn: Option<String> = None;
+    let mut osymfn: Option<String> = None;
+    { // this block limits scope of borrows by ap.refer() method
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Draws native FSTs in GraphViz DOT format (symbol tables in the FST are used if present).");
+        ap.refer(&mut isymfn)
+            .add_option(&["-i", "--loadisfn"], StoreOption, "Load input symbol table from filename");
+        ap.refer(&mut osymfn)
+            .add_option(&["-o", "--loadosfn"], StoreOption, "Load output symbol table from filename");
+        ap.refer(&mut options.acceptor)
+            .add_option(&["-a", "--acceptor"], StoreTrue, "Draw an acceptor (input labels only)");
+        ap.refer(&mut options.portrait)
+            .add_option(&["-p", "--portrait"], StoreTrue, "Portrait orientation (default is landscape)");
+        ap.refer(&mut title)
+            .add_option(&["-t", "--title"], StoreOption, "Title of the drawing");
+        ap.refer(&mut options.max_label_len)
+            .add_option(&["-l", "--maxlabellen"], StoreOption, "Truncate labels longer than this");
+        ap.parse_args_or_exit();
+    }
+    if let Some(title) = title {
+        options.title = title;
+    }
+
+    let isyms = load_syms(isymfn);
+    let osyms = load_syms(osymfn);
+
+    //Read STDIN (decompressing if necessary), archives are read into
+    //memory for random access
+    let stdin = io::stdin();
+    let result = decompress(stdin.lock()).and_then(|input| {
+        let mut input = peek(input, ARCHIVE_MAGIC.len())?;
+        if is_archive(input.fill_buf()?) {
+            let mut buffer = Vec::new();
+            input.read_to_end(&mut buffer)?;
+            wfstdraw_archive(buffer, isyms, osyms, &options)
+        } else {
+            let w = deserialise_wrapper_from(input)?;
+            wfstio_wrapper_apply!(w, fst, wfstdraw(fst, isyms, osyms, &options))
+        }
+    });
+    match result {
+        Ok(_) => (),
+        Err(e) => { eprintln!("{}", e);
+                    exit(EXCODE_BADINPUT);
+        },
+    };
+}
@@ -4,62 +4,24 @@ use argparse::{ArgumentParser, StoreTrue, StoreOption};
 #[macro_use]
 extern crate wfst;
 use wfst::semiring::Weight;
-use wfst::MutableFst;
+use wfst::{MutableFst, ExpandedFst};
+use wfst::symboltable::SymbolTable;
 
-use std::any::TypeId;
 use std::fmt::Display;
-use std::fs::File;
-use std::io::{self, Read, Write, BufWriter};
+use std::io::{self, BufRead, Read, Cursor};
+use std::rc::Rc;
 use std::process::exit;
 
-use wfst::wfst_io::IOError;
+use wfst::Error;
+use wfst::wfst_io::deserialise_wrapper_from;
+use wfst::wfst_io::compression::{decompress, peek};
+use wfst::wfst_io::text::write_att;
+use wfst::wfst_io::archive::{is_archive, ArchiveReader, ARCHIVE_MAGIC};
 
-//needed to use `wfstio_autodeserialise_apply` macro...
-extern crate bincode;
 
 const EXCODE_BADINPUT: i32 = 2;
 
-//DEMIT TODO: Remove duplication
-fn load_symtab(symfn: &str) -> Result<Vec<String>, IOError> {
-    //Slurp lines to parsed fields (DEMITMEM)
-    let mut fh = File::open(symfn)?;
-    let mut s = String::new();
-    fh.read_to_string(&mut s)?;
-    let mut entries = Vec::new();
-    let mut n: usize = 0;
-    for line in s.lines() {
-        let fields = line.split_whitespace().collect::<Vec<_>>();
-        if fields.len() != 2 {
-            return Err(IOError{message: format!("Format error: wrong number of fields")})
-        }
-        let entry = (fields[1].parse::<usize>()?, String::from(fields[0]));
-        if entry.0 > n {
-            n = entry.0;
-        }
-        entries.push(entry)
-    }
-    let mut syms = Vec::with_capacity(n+1);
-    for _ in 0..n+1 {
-        syms.push(String::new());
-    }
-    for entry in entries {
-        syms[entry.0] = entry.1;
-    }
-    Ok(syms)
-}
-
-
-fn save_symtab(symtab: Vec<String>, symfn: &str) -> Result<(), IOError> {
-    let fh = File::create(symfn)?;
-    let mut fh = BufWriter::new(fh);
-    for (i, s) in symtab.into_iter().enumerate() {
-        fh.write(format!("{}\t{}\n", s, i).as_bytes())?;
-    }
-    Ok(())
-}
-
-
-fn wfstprint<W: Weight, F: MutableFst<W> + Display>(mut fst: F, isyms: Option<Vec<String>>, osyms: Option<Vec<String>>, isymfn: Option<String>, osymfn: Option<String>, mapsyms: bool) -> Result<(), IOError> {
+fn wfstprint<W: Weight + Display, F: MutableFst<W> + ExpandedFst<W>>(mut fst: F, isyms: Option<Rc<SymbolTable>>, osyms: Option<Rc<SymbolTable>>, isymfn: Option<String>, osymfn: Option<String>, mapsyms: bool, acceptor: bool) -> Result<(), Error> {
 
     if let Some(syms) = isyms {
         fst.set_isyms(syms);
@@ -70,15 +32,13 @@ fn wfstprint<W: Weight, F: MutableFst<W> + Display>(mut fst: F, isyms: Option<Ve
 
     if let Some(symfn) = isymfn {
         if let Some(syms) = fst.get_isyms() {
-            let symtab: Vec<String> = syms.into_iter().collect();
-            save_symtab(symtab, &symfn)?;
+            syms.save(&symfn)?;
         }
     }
 
     if let Some(symfn) = osymfn {
         if let Some(syms) = fst.get_osyms() {
-            let symtab: Vec<String> = syms.into_iter().collect();
-            save_symtab(symtab, &symfn)?;
+            syms.save(&symfn)?;
         }
     }
     
@@ -87,7 +47,19 @@ fn wfstprint<W: Weight, F: MutableFst<W> + Display>(mut fst: F, isyms: Option<Ve
         fst.del_osyms();
     }
     
-    println!("{}", fst);
+    let stdout = io::stdout();
+    write_att(&fst, &mut stdout.lock(), acceptor)
+}
+
+//Prints each Fst in an archive after its key, followed by an empty line
+fn wfstprint_archive(buffer: Vec<u8>, isyms: Option<Rc<SymbolTable>>, osyms: Option<Rc<SymbolTable>>, isymfn: Option<String>, osymfn: Option<String>, mapsyms: bool, acceptor: bool) -> Result<(), Error> {
+    let mut archive = ArchiveReader::new(Cursor::new(buffer))?;
+    for record in archive.iter() {
+        let (key, w) = record?;
+        println!("{}", key);
+        wfstio_wrapper_apply!(w, fst, wfstprint(fst, isyms.clone(), osyms.clone(), isymfn.clone(), osymfn.clone(), mapsyms, acceptor))?;
+        println!();
+    }
     Ok(())
 }
 
@@ -96,6 +68,7 @@ fn wfstprint<W: Weight, F: MutableFst<W> + Display>(mut fst: F, isyms: Option<Ve
 fn main() {
     //Setup defaults and parse args
     let mut mapsyms = false;
+    let mut acceptor = false;
     let mut isymfn: Option<String> = None;
     let mut osymfn: Option<String> = None;
     let mut saveisymfn: Option<String> = None;
@@ -113,44 +86,49 @@ fn main() {
             .add_option(&["-O", "--saveosfn"], StoreOption, "Save output symbol table to filename");
         ap.refer(&mut mapsyms)
             .add_option(&["-m", "--mapsyms"], StoreTrue, "Map symbols using symbol tables (default is to output integer symbols)");
+        ap.refer(&mut acceptor)
+            .add_option(&["-a", "--acceptor"], StoreTrue, "Print an acceptor (input labels only)");
         
         ap.parse_args_or_exit();
     }
 
-    //Slurp STDIN (DEMITMEM)
-    let stdin = io::stdin();
-    let mut handle = stdin.lock();
-    let mut buffer = Vec::new();
-    match handle.read_to_end(&mut buffer) {
-        Ok(_) => (),
-        Err(e) => { eprintln!("{}", e);
-                        exit(EXCODE_BADINPUT);
-        },
-    };
-
     //Try to load symtabs?
     let isyms = match isymfn {
-        Some(symfn) => match load_symtab(&symfn) {
-            Ok(syms) => Some(syms),
-            Err(e) => { eprintln!("{}", e.message);
+        Some(symfn) => match SymbolTable::load(&symfn) {
+            Ok(syms) => Some(Rc::new(syms)),
+            Err(e) => { eprintln!("{}", e);
                         exit(EXCODE_BADINPUT);
             },
         },
         None => None,
     };
     let osyms = match osymfn {
-        Some(symfn) => match load_symtab(&symfn) {
-            Ok(syms) => Some(syms),
-            Err(e) => { eprintln!("{}", e.message);
+        Some(symfn) => match SymbolTable::load(&symfn) {
+            Ok(syms) => Some(Rc::new(syms)),
+            Err(e) => { eprintln!("{}", e);
                         exit(EXCODE_BADINPUT);
             },
         },
         None => None,
     };
 
-    match wfstio_autodeserialise_apply!(buffer, fst, wfstprint(fst, isyms, osyms, saveisymfn, saveosymfn, mapsyms)) {
+    //Read STDIN (decompressing if necessary), archives are read into
+    //memory for random access
+    let stdin = io::stdin();
+    let result = decompress(stdin.lock()).and_then(|input| {
+        let mut input = peek(input, ARCHIVE_MAGIC.len())?;
+        if is_archive(input.fill_buf()?) {
+            let mut buffer = Vec::new();
+            input.read_to_end(&mut buffer)?;
+            wfstprint_archive(buffer, isyms, osyms, saveisymfn, saveosymfn, mapsyms, acceptor)
+        } else {
+            let w = deserialise_wrapper_from(input)?;
+            wfstio_wrapper_apply!(w, fst, wfstprint(fst, isyms, osyms, saveisymfn, saveosymfn, mapsyms, acceptor))
+        }
+    });
+    match result {
         Ok(_) => (),
-        Err(e) => { eprintln!("{}", e.message);
+        Err(e) => { eprintln!("{}", e);
                     exit(EXCODE_BADINPUT);
         },
     };